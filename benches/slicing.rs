@@ -0,0 +1,154 @@
+// Measures the parser and the slicing engine on synthetic inputs. Run with
+// `cargo bench`; a filter argument only runs the benchmarks whose names contain it,
+// e.g. `cargo bench -- unicode`.
+use std::env;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use common::Random;
+use rtr::fast_path::FastPath;
+use rtr::grid_slice::grid_slice_iter;
+use rtr::mmap::MmapLines;
+use rtr::parser::grid_slice_parser::parse_grid_slice;
+
+// Every measurement is repeated until it takes at least this long.
+const MIN_DURATION: Duration = Duration::from_millis(500);
+
+struct Input {
+    name: &'static str,
+    data: String,
+    lines: usize,
+}
+
+fn generate(name: &'static str, lines: usize, fields: usize, alphabet: &[char]) -> Input {
+    let mut random = Random(42);
+    let mut data = String::new();
+    for _ in 0..lines {
+        for n in 0..fields {
+            if n > 0 {
+                data.push(' ');
+            }
+            for _ in 0..1 + random.next(8) {
+                data.push(alphabet[random.next(alphabet.len())]);
+            }
+        }
+        data.push('\n');
+    }
+    Input { name, data, lines }
+}
+
+fn inputs() -> Vec<Input> {
+    let ascii: Vec<char> = ('a'..='z').chain('0'..='9').collect();
+    let unicode: Vec<char> = "абвгдежзийклмнопрстуфхцчшщэюяαβγδεζηθ日本語文字列ÿñç"
+        .chars()
+        .collect();
+    vec![
+        generate("many lines", 200_000, 5, &ascii),
+        generate("wide lines", 5_000, 200, &ascii),
+        generate("long lines", 200, 5_000, &ascii),
+        generate("unicode", 50_000, 10, &unicode),
+    ]
+}
+
+// Runs `f` until `MIN_DURATION` passes and returns the average time of a run.
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || start.elapsed() < MIN_DURATION {
+        f();
+        runs += 1;
+    }
+    start.elapsed() / runs
+}
+
+fn bench_parser(filter: &str) {
+    let slices = [
+        "F1",
+        "l1:l-1f-1",
+        "l0f1c0:l-1f1c-1:l2",
+        "!f[0,2,-1]c[1,3]",
+        "p1l0f-1c0:p1l10f-1c5",
+    ];
+    for slice in &slices {
+        let name = format!("parse {}", slice);
+        if !name.contains(filter) {
+            continue;
+        }
+        let time = measure(|| {
+            black_box(parse_grid_slice(black_box(slice)).unwrap());
+        });
+        println!("{:<40} {:>12.0?}", name, time);
+    }
+}
+
+fn bench_slicing(filter: &str, input: &Input) {
+    // The ones counting from the end of the input or stepping backwards save all the
+    // lines first.
+    let slices = [
+        "l0",
+        "F1",
+        "f-1",
+        "f1:f3c0:c2",
+        "!f0:f2",
+        "f[0,-1]",
+        "l0f0:l-1f0:l2",
+        "l-100",
+        "L-1",
+        "l0f1::l-1",
+        "!l0:l9",
+    ];
+    for slice in &slices {
+        let name = format!("{}: {}", input.name, slice);
+        if !name.contains(filter) {
+            continue;
+        }
+        let grid_slice = parse_grid_slice(slice).unwrap();
+        let time = measure(|| {
+            let lines = MmapLines::new(input.data.as_bytes(), b"\n");
+            for line in grid_slice_iter(grid_slice.clone(), lines) {
+                black_box(line);
+            }
+        });
+        report(&name, input, time);
+
+        // The streaming path only handles line ranges that don't need post-processing.
+        if rtr::grid_slice::lines_need_post_process(&grid_slice) {
+            continue;
+        }
+        let name = format!("{} (fast path)", name);
+        let mut out = Vec::new();
+        let time = measure(|| {
+            out.clear();
+            FastPath::new(&grid_slice, b"\n", b"\n", false)
+                .run(input.data.as_bytes(), 0, &mut out)
+                .unwrap();
+            black_box(&out);
+        });
+        report(&name, input, time);
+    }
+}
+
+fn report(name: &str, input: &Input, time: Duration) {
+    println!(
+        "{:<40} {:>12.2?} {:>12.0} lines/s {:>8.1} MiB/s",
+        name,
+        time,
+        input.lines as f64 / time.as_secs_f64(),
+        input.data.len() as f64 / time.as_secs_f64() / (1 << 20) as f64
+    );
+}
+
+fn main() {
+    // `cargo bench` passes `--bench`.
+    let filter = env::args()
+        .skip(1)
+        .find(|a| !a.starts_with("--"))
+        .unwrap_or_default();
+    bench_parser(&filter);
+    for input in inputs() {
+        bench_slicing(&filter, &input);
+    }
+}
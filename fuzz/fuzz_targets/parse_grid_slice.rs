@@ -0,0 +1,41 @@
+// Run with `cargo fuzz run parse_grid_slice` from the root of the repository.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+use rtr::fast_path::FastPath;
+use rtr::grid_slice::{grid_slice_iter, lines_need_post_process};
+use rtr::parser::grid_slice_parser::{
+    parse_exclusive_grid_slice, parse_grid_slice, GridSliceFilter,
+};
+use rtr::records::SplitLines;
+
+const INPUT: &str = "a bc def\n\nghij k\nl\n";
+
+fuzz_target!(|data: &[u8]| {
+    let input = match std::str::from_utf8(data) {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    check(input, parse_grid_slice);
+    check(input, parse_exclusive_grid_slice);
+});
+
+fn check(input: &str, parse: fn(&str) -> Result<GridSliceFilter, &'static str>) {
+    // Parsing must never panic and whatever is parsed must be printed back to an
+    // equivalent slice.
+    let grid_slice = match parse(input) {
+        Ok(g) => g,
+        Err(_) => return,
+    };
+    let text = grid_slice.to_string();
+    assert_eq!(parse(&text).as_ref(), Ok(&grid_slice), "{}", text);
+
+    // Neither should slicing with it.
+    if !lines_need_post_process(&grid_slice) {
+        FastPath::new(&grid_slice, b"\n", b"\n", false)
+            .run(INPUT.as_bytes(), 0, &mut Vec::new())
+            .unwrap();
+    }
+    let lines = SplitLines::new(INPUT.as_bytes(), b"\n");
+    grid_slice_iter(grid_slice, lines).for_each(drop);
+}
@@ -0,0 +1,219 @@
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Reducer {
+    Count,
+    Sum,
+    Min,
+    Max,
+    Mean,
+    First,
+    Last,
+}
+
+impl Reducer {
+    pub fn name(self) -> &'static str {
+        match self {
+            Reducer::Count => "count",
+            Reducer::Sum => "sum",
+            Reducer::Min => "min",
+            Reducer::Max => "max",
+            Reducer::Mean => "mean",
+            Reducer::First => "first",
+            Reducer::Last => "last",
+        }
+    }
+}
+
+// Parses a comma separated list of reducers (e.g. "sum,count").
+pub fn parse_reducers(input: &str) -> Result<Vec<Reducer>, &'static str> {
+    input
+        .split(',')
+        .map(|name| match name {
+            "count" => Ok(Reducer::Count),
+            "sum" => Ok(Reducer::Sum),
+            "min" => Ok(Reducer::Min),
+            "max" => Ok(Reducer::Max),
+            "mean" => Ok(Reducer::Mean),
+            "first" => Ok(Reducer::First),
+            "last" => Ok(Reducer::Last),
+            _ => Err("Unknown aggregate function"),
+        })
+        .collect()
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NonNumeric {
+    #[default]
+    Skip,
+    Error,
+}
+
+pub fn parse_non_numeric(input: &str) -> Result<NonNumeric, &'static str> {
+    match input {
+        "skip" => Ok(NonNumeric::Skip),
+        "error" => Ok(NonNumeric::Error),
+        _ => Err("Unknown non-numeric policy"),
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Accumulator {
+    count: usize,
+    sum: f64,
+    min: f64,
+    max: f64,
+    first: f64,
+    last: f64,
+}
+
+impl Accumulator {
+    pub fn add(&mut self, value: f64) {
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+            self.first = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.sum += value;
+        self.last = value;
+        self.count += 1;
+    }
+
+    // Returns None if there is nothing to compute the value from.
+    pub fn value(&self, reducer: Reducer) -> Option<f64> {
+        match reducer {
+            Reducer::Count => Some(self.count as f64),
+            Reducer::Sum => Some(self.sum),
+            _ if self.count == 0 => None,
+            Reducer::Min => Some(self.min),
+            Reducer::Max => Some(self.max),
+            Reducer::Mean => Some(self.sum / self.count as f64),
+            Reducer::First => Some(self.first),
+            Reducer::Last => Some(self.last),
+        }
+    }
+}
+
+// Prints integral values without the fractional part.
+pub fn format_number(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v.fract() == 0.0 && v.abs() < 1e15 => format!("{}", v as i64),
+        Some(v) => v.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+pub fn parse_number(cell: &str, non_numeric: NonNumeric) -> Result<Option<f64>, &'static str> {
+    match cell.parse::<f64>() {
+        Ok(v) if v.is_finite() => Ok(Some(v)),
+        _ if non_numeric == NonNumeric::Skip => Ok(None),
+        _ => Err("Non-numeric value found"),
+    }
+}
+
+pub struct Aggregation {
+    reducers: Vec<Reducer>,
+    per_column: bool,
+    non_numeric: NonNumeric,
+    columns: Vec<Accumulator>,
+}
+
+impl Aggregation {
+    pub fn new(reducers: Vec<Reducer>, per_column: bool, non_numeric: NonNumeric) -> Self {
+        Aggregation {
+            reducers,
+            per_column,
+            non_numeric,
+            columns: Vec::new(),
+        }
+    }
+
+    pub fn add<S: AsRef<str>>(&mut self, cells: &[S]) -> Result<(), &'static str> {
+        let width = if self.per_column { cells.len() } else { 1 };
+        if self.columns.len() < width {
+            self.columns.resize(width, Accumulator::default());
+        }
+        for (n, cell) in cells.iter().enumerate() {
+            if let Some(v) = parse_number(cell.as_ref(), self.non_numeric)? {
+                let column = if self.per_column { n } else { 0 };
+                self.columns[column].add(v);
+            }
+        }
+        Ok(())
+    }
+
+    // Returns a line per reducer that starts with the reducer's name followed by its
+    // value for each column.
+    pub fn summary(&self) -> Vec<Vec<String>> {
+        let columns = if self.columns.is_empty() && !self.per_column {
+            vec![Accumulator::default()]
+        } else {
+            self.columns.clone()
+        };
+        self.reducers
+            .iter()
+            .map(|r| {
+                std::iter::once(r.name().to_string())
+                    .chain(columns.iter().map(|c| format_number(c.value(*r))))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn aggregation_test() {
+        let reducers = parse_reducers("count,sum,min,max,mean,first,last").unwrap();
+        let mut all = Aggregation::new(reducers.clone(), false, NonNumeric::Skip);
+        let mut per_column = Aggregation::new(reducers, true, NonNumeric::Skip);
+        for line in [cells(&["1", "x"]), cells(&["2", "4.5", "-3"])].iter() {
+            all.add(line).unwrap();
+            per_column.add(line).unwrap();
+        }
+        assert_eq!(
+            all.summary(),
+            vec![
+                cells(&["count", "4"]),
+                cells(&["sum", "4.5"]),
+                cells(&["min", "-3"]),
+                cells(&["max", "4.5"]),
+                cells(&["mean", "1.125"]),
+                cells(&["first", "1"]),
+                cells(&["last", "-3"]),
+            ]
+        );
+        assert_eq!(
+            per_column.summary(),
+            vec![
+                cells(&["count", "2", "1", "1"]),
+                cells(&["sum", "3", "4.5", "-3"]),
+                cells(&["min", "1", "4.5", "-3"]),
+                cells(&["max", "2", "4.5", "-3"]),
+                cells(&["mean", "1.5", "4.5", "-3"]),
+                cells(&["first", "1", "4.5", "-3"]),
+                cells(&["last", "2", "4.5", "-3"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn aggregation_non_numeric_test() {
+        let mut aggregation = Aggregation::new(vec![Reducer::Sum], false, NonNumeric::Error);
+        assert!(aggregation.add(&cells(&["1", "x"])).is_err());
+        let empty = Aggregation::new(vec![Reducer::Sum, Reducer::Max], false, NonNumeric::Skip);
+        assert_eq!(
+            empty.summary(),
+            vec![cells(&["sum", "0"]), cells(&["max", "-"])]
+        );
+        assert!(parse_reducers("sum,avg").is_err());
+    }
+}
@@ -0,0 +1,129 @@
+use std::collections::VecDeque;
+
+use crate::grid_slice::Line;
+
+pub enum ContextLine<'a> {
+    Selected(Line<'a>),
+    Context(Line<'a>),
+    // Separates groups of lines that are not adjacent in the input.
+    Separator,
+}
+
+pub struct Context<'a, I> {
+    source: I,
+    before: usize,
+    after: usize,
+    // Skipped lines that could be printed as the context of the next selected line.
+    before_lines: VecDeque<Line<'a>>,
+    after_left: usize,
+    pending: VecDeque<ContextLine<'a>>,
+    num_line: usize,
+    last_printed: Option<usize>,
+}
+
+impl<'a, I: Iterator<Item = Line<'a>>> Context<'a, I> {
+    fn push_selected(&mut self, line: Line<'a>) {
+        let first = self.num_line - self.before_lines.len();
+        if let Some(last) = self.last_printed {
+            if first > last + 1 {
+                self.pending.push_back(ContextLine::Separator);
+            }
+        }
+        self.pending
+            .extend(self.before_lines.drain(..).map(ContextLine::Context));
+        self.pending.push_back(ContextLine::Selected(line));
+        self.after_left = self.after;
+        self.last_printed = Some(self.num_line);
+    }
+
+    fn push_skipped(&mut self, line: Line<'a>) {
+        if self.after_left > 0 {
+            self.after_left -= 1;
+            self.pending.push_back(ContextLine::Context(line));
+            self.last_printed = Some(self.num_line);
+        } else if self.before > 0 {
+            if self.before_lines.len() == self.before {
+                self.before_lines.pop_front();
+            }
+            self.before_lines.push_back(line);
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = Line<'a>>> Iterator for Context<'a, I> {
+    type Item = ContextLine<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            let line = self.source.next()?;
+            if line.selected {
+                self.push_selected(line);
+            } else {
+                self.push_skipped(line);
+            }
+            self.num_line += 1;
+        }
+        self.pending.pop_front()
+    }
+}
+
+// Expects `iter` to return skipped lines as well (see `GridSlice::with_skipped_lines()`).
+pub fn context_iter<'a, I: Iterator<Item = Line<'a>>>(
+    iter: I,
+    before: usize,
+    after: usize,
+) -> Context<'a, I> {
+    Context {
+        source: iter,
+        before,
+        after,
+        before_lines: VecDeque::new(),
+        after_left: 0,
+        pending: VecDeque::new(),
+        num_line: 0,
+        last_printed: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(number: usize, selected: bool) -> Line<'static> {
+        Line {
+            number,
+            fields: vec![number.to_string().into()],
+            cells: Vec::new(),
+            selected,
+        }
+    }
+
+    fn render(lines: &[bool], before: usize, after: usize) -> Vec<String> {
+        let lines = lines.iter().enumerate().map(|(n, s)| line(n, *s));
+        context_iter(lines, before, after)
+            .map(|l| match l {
+                ContextLine::Selected(l) => format!("{}:", l.number),
+                ContextLine::Context(l) => format!("{}-", l.number),
+                ContextLine::Separator => "--".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn context_iter_test() {
+        let lines = [false, false, true, false, false, false, false, true, false];
+        assert_eq!(render(&lines, 0, 0), vec!["2:", "--", "7:"]);
+        assert_eq!(
+            render(&lines, 1, 1),
+            vec!["1-", "2:", "3-", "--", "6-", "7:", "8-"]
+        );
+        assert_eq!(
+            render(&lines, 5, 0),
+            vec!["0-", "1-", "2:", "3-", "4-", "5-", "6-", "7:"]
+        );
+        assert_eq!(
+            render(&lines, 0, 3),
+            vec!["2:", "3-", "4-", "5-", "--", "7:", "8-"]
+        );
+    }
+}
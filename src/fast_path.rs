@@ -0,0 +1,212 @@
+use std::borrow::Cow;
+use std::io::{self, BufRead, Write};
+
+use crate::grid_slice::{
+    filter_by_range, filter_by_ranges, merge_ranges, normalize_index, normalize_range, slice_str,
+    RangeSetCache, UNKNOWN_LENGTH,
+};
+use crate::parser::grid_slice_parser::GridSliceFilter;
+use crate::records::read_record;
+
+// The same selection as `grid_slice::slice_fields` does, but only the indexes of the
+// fields are returned.
+fn select_fields(
+    grid_slice: &GridSliceFilter,
+    len: usize,
+    selected: &mut Vec<usize>,
+    fields: &mut RangeSetCache,
+) {
+    let field = &grid_slice.field;
+    selected.clear();
+    if let Some(ref set) = grid_slice.field_set {
+        let ranges = fields.get(set, len);
+        selected.extend((0..len).filter(|n| filter_by_ranges(ranges, *n as i64)));
+        return;
+    }
+    if let Some(ref list) = grid_slice.field_list {
+        if field.exclude {
+            selected.extend(
+                (0..len).filter(|n| !list.iter().any(|i| normalize_index(*i, len) == Some(*n))),
+            );
+        } else {
+            selected.extend(list.iter().filter_map(|i| normalize_index(*i, len)));
+        }
+        return;
+    }
+    let range = normalize_range(field, len);
+    let keep = |(k, _): &(usize, usize)| filter_by_range(&range, *k as i64, field.exclude);
+    if range.step > 0 {
+        selected.extend((0..len).enumerate().filter(keep).map(|(_, n)| n));
+    } else {
+        selected.extend((0..len).rev().enumerate().filter(keep).map(|(_, n)| n));
+    }
+}
+
+// Selected fields are found by their offsets in the line, so no memory is allocated
+// per line once the buffers have grown to fit the longest line. The line range or set
+// must not need post-processing.
+pub struct FastPath<'a> {
+    grid_slice: &'a GridSliceFilter,
+    separator: &'a [u8],
+    record_separator: &'a [u8],
+    line_numbers: bool,
+    record: Vec<u8>,
+    // Start and end offsets of the fields in the current line.
+    spans: Vec<(usize, usize)>,
+    // Indexes of the fields to print in the order they have to be printed.
+    selected: Vec<usize>,
+    // Merged field and character sets.
+    fields: RangeSetCache,
+    characters: RangeSetCache,
+}
+
+impl<'a> FastPath<'a> {
+    pub fn new(
+        grid_slice: &'a GridSliceFilter,
+        separator: &'a [u8],
+        record_separator: &'a [u8],
+        line_numbers: bool,
+    ) -> Self {
+        FastPath {
+            grid_slice,
+            separator,
+            record_separator,
+            line_numbers,
+            record: Vec::new(),
+            spans: Vec::new(),
+            selected: Vec::new(),
+            fields: RangeSetCache::default(),
+            characters: RangeSetCache::default(),
+        }
+    }
+
+    // `first_line` is the index of the first line of `reader` in the whole input.
+    pub fn run<R: BufRead, W: Write>(
+        &mut self,
+        mut reader: R,
+        first_line: usize,
+        out: &mut W,
+    ) -> io::Result<()> {
+        let line_range = &self.grid_slice.line;
+        let line_ranges = self
+            .grid_slice
+            .line_set
+            .as_ref()
+            .map(|s| merge_ranges(s, UNKNOWN_LENGTH));
+        let field = &self.grid_slice.field;
+        // Fields after the end of a range that doesn't count from the end of the line
+        // are not needed.
+        let limit = if self.grid_slice.field_list.is_none()
+            && self.grid_slice.field_set.is_none()
+            && !field.exclude
+            && field.from >= 0
+            && field.to >= 0
+            && field.step > 0
+        {
+            (field.to as usize).saturating_add(1)
+        } else {
+            usize::MAX
+        };
+        let mut number = first_line;
+        while read_record(&mut reader, self.separator, &mut self.record)? {
+            number += 1;
+            let selected = match line_ranges {
+                Some(ref ranges) => filter_by_ranges(ranges, number as i64 - 1),
+                None => filter_by_range(line_range, number as i64 - 1, line_range.exclude),
+            };
+            if !selected {
+                // Nothing could be selected after the end of the range.
+                if line_ranges.is_none()
+                    && !line_range.exclude
+                    && line_range.to != -1
+                    && number as i64 > line_range.to
+                {
+                    break;
+                }
+                continue;
+            }
+            // Only invalid UTF-8 is copied.
+            let line: Cow<str> = String::from_utf8_lossy(&self.record);
+            self.spans.clear();
+            self.spans
+                .extend(line.split_whitespace().take(limit).map(|f| {
+                    let start = f.as_ptr() as usize - line.as_ptr() as usize;
+                    (start, start + f.len())
+                }));
+            select_fields(
+                self.grid_slice,
+                self.spans.len(),
+                &mut self.selected,
+                &mut self.fields,
+            );
+            if self.line_numbers {
+                write!(out, "{}:", number - 1)?;
+            }
+            for (k, n) in self.selected.iter().enumerate() {
+                if k > 0 {
+                    out.write_all(b" ")?;
+                }
+                let (start, end) = self.spans[*n];
+                out.write_all(
+                    slice_str(self.grid_slice, &line[start..end], &mut self.characters).as_bytes(),
+                )?;
+            }
+            out.write_all(self.record_separator)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid_slice::grid_slice_iter;
+    use crate::parser::grid_slice_parser::parse_grid_slice;
+    use crate::records::SplitLines;
+
+    const INPUT: &str = "a bb ccc\n\n1 22 333 4444 55555\nx y\n  q  r \nlast\n";
+
+    fn fast_path(slice: &str) -> String {
+        let grid_slice = parse_grid_slice(slice).unwrap();
+        let mut out = Vec::new();
+        FastPath::new(&grid_slice, b"\n", b"\n", false)
+            .run(INPUT.as_bytes(), 0, &mut out)
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn grid_slice(slice: &str) -> String {
+        let lines = SplitLines::new(INPUT.as_bytes(), b"\n");
+        grid_slice_iter(parse_grid_slice(slice).unwrap(), lines)
+            .map(|l| format!("{}\n", l.cells.join(" ")))
+            .collect()
+    }
+
+    #[test]
+    fn fast_path_test() {
+        let slices = vec![
+            "l0",
+            "L2",
+            "l1:l3",
+            "!l1:l3",
+            "l0::l2",
+            "f1",
+            "F-1",
+            "f0::f-1",
+            "f-2:f0:f-1",
+            "!F1",
+            "f[2,0,-1]",
+            "!f[0,-1]",
+            "f0c1",
+            "F0c-1",
+            "f0c0::c-2",
+            "f0c[0,0]",
+            "!c0:c1",
+            "l1:l4F1c1:c2",
+            "L9",
+        ];
+        for slice in slices {
+            assert_eq!(fast_path(slice), grid_slice(slice), "{}", slice);
+        }
+    }
+}
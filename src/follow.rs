@@ -0,0 +1,163 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+// Reads a file that keeps growing the same way `tail -F` does. Reading never reaches
+// the end of the input: it waits for new data instead. A file that was truncated is
+// read again from the beginning and a file that was replaced with another one (e.g.
+// during log rotation) is reopened.
+pub struct Follow {
+    path: String,
+    file: File,
+    position: u64,
+    id: Option<u64>,
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.ino())
+}
+
+// Rotation can't be detected without inodes, only truncation.
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+// Returns the offset of the `count`-th record from the end of the input.
+fn tail_offset<R: BufRead>(mut reader: R, separator: &[u8], count: usize) -> io::Result<u64> {
+    let last = match separator.last() {
+        Some(b) => *b,
+        None => return Ok(0),
+    };
+    let mut starts = VecDeque::with_capacity(count + 1);
+    let mut position = 0;
+    let mut record = Vec::new();
+    loop {
+        record.clear();
+        while reader.read_until(last, &mut record)? > 0 && !record.ends_with(separator) {}
+        if record.is_empty() {
+            break;
+        }
+        starts.push_back(position);
+        if starts.len() > count {
+            starts.pop_front();
+        }
+        position += record.len() as u64;
+    }
+    Ok(starts.front().copied().unwrap_or(position))
+}
+
+impl Follow {
+    // Starts reading from the last `last` records or from the beginning if not set.
+    pub fn open(path: &str, separator: &[u8], last: Option<usize>) -> io::Result<Self> {
+        let mut file = File::open(path)?;
+        let position = match last {
+            Some(count) => tail_offset(BufReader::new(&file), separator, count)?,
+            None => 0,
+        };
+        file.seek(SeekFrom::Start(position))?;
+        let id = file_id(&file.metadata()?);
+        Ok(Follow {
+            path: path.to_string(),
+            file,
+            position,
+            id,
+        })
+    }
+
+    // Returns true if the file was replaced and successfully reopened.
+    fn reopen_if_rotated(&mut self) -> bool {
+        let id = match fs::metadata(&self.path) {
+            Ok(m) => file_id(&m),
+            // The new file might not be created yet.
+            Err(_) => return false,
+        };
+        if id == self.id {
+            return false;
+        }
+        match File::open(&self.path) {
+            Ok(file) => {
+                self.file = file;
+                self.position = 0;
+                self.id = id;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl Read for Follow {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.file.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                self.position += n as u64;
+                return Ok(n);
+            }
+            // The rest of the old file has been read by now, so it's safe to switch.
+            if self.reopen_if_rotated() {
+                continue;
+            }
+            if self.file.metadata()?.len() < self.position {
+                self.position = self.file.seek(SeekFrom::Start(0))?;
+                continue;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn tail_offset_test() {
+        let input = "a\nbb\nccc\n";
+        assert_eq!(tail_offset(input.as_bytes(), b"\n", 0).unwrap(), 9);
+        assert_eq!(tail_offset(input.as_bytes(), b"\n", 1).unwrap(), 5);
+        assert_eq!(tail_offset(input.as_bytes(), b"\n", 2).unwrap(), 2);
+        assert_eq!(tail_offset(input.as_bytes(), b"\n", 5).unwrap(), 0);
+        assert_eq!(tail_offset("a;;b;;c".as_bytes(), b";;", 2).unwrap(), 3);
+    }
+
+    #[test]
+    fn follow_test() {
+        let path = std::env::temp_dir().join(format!("rtr-follow-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, "a\nb\nc\n").unwrap();
+        let mut follow = Follow::open(path, b"\n", Some(2)).unwrap();
+        let mut buf = [0; 16];
+        assert_eq!(follow.read(&mut buf).unwrap(), 4);
+        assert_eq!(&buf[..4], b"b\nc\n");
+
+        let mut file = fs::OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(b"d\n").unwrap();
+        assert_eq!(follow.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"d\n");
+
+        // Truncated.
+        fs::write(path, "e\n").unwrap();
+        assert_eq!(follow.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], b"e\n");
+
+        // Rotated.
+        if cfg!(unix) {
+            let rotated = format!("{}.1", path);
+            fs::rename(path, &rotated).unwrap();
+            fs::write(path, "f\n").unwrap();
+            assert_eq!(follow.read(&mut buf).unwrap(), 2);
+            assert_eq!(&buf[..2], b"f\n");
+            fs::remove_file(rotated).unwrap();
+        }
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -1,166 +1,512 @@
+use std::borrow::Cow;
+
 use crate::parser::grid_slice_parser;
+use crate::predicate::LineFilter;
+
+// A field or a part of it. Cells are borrowed when the input is mapped into memory and
+// owned when it's read line by line.
+pub type Cell<'a> = Cow<'a, str>;
 
-enum GridSliceSource<I> {
+enum GridSliceSource<'a, I> {
     Iter(I),
-    SavedLines(Vec<Vec<String>>),
+    SavedLines(std::vec::IntoIter<Vec<Cell<'a>>>),
+}
+
+pub struct Line<'a> {
+    // Index of the line in the input.
+    pub number: usize,
+    // All fields of the line as they were read.
+    pub fields: Vec<Cell<'a>>,
+    // Fields and characters selected by the slice.
+    pub cells: Vec<Cell<'a>>,
+    // Whether the line was selected by the slice. Only skipped lines have it set to
+    // false and they are returned only when requested with `with_skipped_lines()`.
+    pub selected: bool,
 }
 
-pub struct GridSlice<I> {
+pub struct GridSlice<'a, I> {
     grid_slice: grid_slice_parser::GridSliceFilter,
-    source: GridSliceSource<I>,
+    source: GridSliceSource<'a, I>,
     num_line: usize,
+    skipped_lines: bool,
+    filter: Option<LineFilter>,
+    // The merged line set, if the slice has one.
+    line_ranges: Option<MergedRanges>,
+    cache: SliceCache,
 }
 
-impl<T> GridSlice<T> {
-    fn slice_fields(&self, fields: Vec<String>) -> Vec<String> {
-        let field_range = normalize_range(&self.grid_slice.field, fields.len());
-        if field_range.step > 0 {
-            self.slice_fields_from_iter(field_range, fields.into_iter())
+impl<T> GridSlice<'_, T> {
+    pub fn with_skipped_lines(mut self) -> Self {
+        self.skipped_lines = true;
+        self
+    }
+
+    // Lines that don't match the filter are skipped the same way as lines outside of
+    // the line range.
+    pub fn with_filter(mut self, mut filter: LineFilter) -> Self {
+        // Saved lines are reversed for negative steps and the header would come last,
+        // so we have to set it in advance.
+        if let GridSliceSource::SavedLines(ref l) = self.source {
+            let lines = l.as_slice();
+            let header = if self.grid_slice.line.step < 0 {
+                lines.last()
+            } else {
+                lines.first()
+            };
+            if let Some(h) = header {
+                filter.set_header(h);
+            }
+        }
+        self.filter = Some(filter);
+        self
+    }
+}
+
+// Range sets merged for the last number of items. Sets that don't count from the end
+// select the same items whatever the number is, so they are merged only once.
+#[derive(Default)]
+pub struct RangeSetCache {
+    merged: Option<(usize, MergedRanges)>,
+}
+
+impl RangeSetCache {
+    pub fn get(&mut self, set: &grid_slice_parser::RangeSet, length: usize) -> &MergedRanges {
+        let length = if set_needs_post_process(set) {
+            length
         } else {
-            self.slice_fields_from_iter(field_range, fields.into_iter().rev())
+            UNKNOWN_LENGTH
+        };
+        if self.merged.as_ref().is_some_and(|(n, _)| *n != length) {
+            self.merged = None;
         }
+        &self
+            .merged
+            .get_or_insert_with(|| (length, merge_ranges(set, length)))
+            .1
     }
+}
 
-    fn slice_fields_from_iter<I: Iterator<Item = String>>(
-        &self,
-        field_range: grid_slice_parser::GridSliceRange,
-        it: I,
-    ) -> Vec<String> {
-        it.enumerate()
-            .filter(|(n, _)| {
-                self.filter_by_range(&field_range, *n as i64, self.grid_slice.field.exclude)
-            })
-            .map(|(_, f)| self.slice_chars(f))
-            .collect()
+// Merged field and character sets of a slice that is applied to many lines.
+#[derive(Default)]
+pub struct SliceCache {
+    fields: RangeSetCache,
+    characters: RangeSetCache,
+}
+
+// Selects fields and characters of a single line. The line range of `grid_slice` is
+// not used here.
+pub fn slice_fields<'a>(
+    grid_slice: &grid_slice_parser::GridSliceFilter,
+    fields: &[Cell<'a>],
+    cache: &mut SliceCache,
+) -> Vec<Cell<'a>> {
+    let SliceCache {
+        fields: field_cache,
+        characters,
+    } = cache;
+    if let Some(ref list) = grid_slice.field_list {
+        return select_by_list(list, grid_slice.field.exclude, fields)
+            .into_iter()
+            .map(|f| slice_chars(grid_slice, f, characters))
+            .collect();
+    }
+    if let Some(ref set) = grid_slice.field_set {
+        let ranges = field_cache.get(set, fields.len());
+        return fields
+            .iter()
+            .enumerate()
+            .filter(|(n, _)| filter_by_ranges(ranges, *n as i64))
+            .map(|(_, f)| slice_chars(grid_slice, f, characters))
+            .collect();
+    }
+    let field_range = normalize_range(&grid_slice.field, fields.len());
+    if field_range.step > 0 {
+        slice_fields_from_iter(grid_slice, field_range, fields.iter(), characters)
+    } else {
+        slice_fields_from_iter(grid_slice, field_range, fields.iter().rev(), characters)
+    }
+}
+
+fn slice_fields_from_iter<'a, 'b, I: Iterator<Item = &'b Cell<'a>>>(
+    grid_slice: &grid_slice_parser::GridSliceFilter,
+    field_range: grid_slice_parser::GridSliceRange,
+    it: I,
+    characters: &mut RangeSetCache,
+) -> Vec<Cell<'a>>
+where
+    'a: 'b,
+{
+    it.enumerate()
+        .filter(|(n, _)| filter_by_range(&field_range, *n as i64, grid_slice.field.exclude))
+        .map(|(_, f)| slice_chars(grid_slice, f, characters))
+        .collect()
+}
+
+// Borrowed fields stay borrowed unless the selected characters are not contiguous.
+fn slice_chars<'a>(
+    grid_slice: &grid_slice_parser::GridSliceFilter,
+    field: &Cell<'a>,
+    characters: &mut RangeSetCache,
+) -> Cell<'a> {
+    match field {
+        Cow::Borrowed(f) => slice_str(grid_slice, f, characters),
+        Cow::Owned(f) => Cow::Owned(slice_str(grid_slice, f, characters).into_owned()),
     }
+}
 
-    fn slice_chars(&self, field: String) -> String {
-        let char_range = normalize_range(&self.grid_slice.character, field.len());
-        if char_range.step > 0 {
-            self.slice_chars_from_iter(char_range, field.chars())
-        } else {
-            self.slice_chars_from_iter(char_range, field.chars().rev())
+pub fn slice_str<'a>(
+    grid_slice: &grid_slice_parser::GridSliceFilter,
+    field: &'a str,
+    characters: &mut RangeSetCache,
+) -> Cell<'a> {
+    if let Some(ref list) = grid_slice.character_list {
+        let chars: Vec<char> = field.chars().collect();
+        return Cow::Owned(
+            select_by_list(list, grid_slice.character.exclude, &chars)
+                .into_iter()
+                .collect(),
+        );
+    }
+    if let Some(ref set) = grid_slice.character_set {
+        let ranges = characters.get(set, field.chars().count());
+        if let Some(range) = ranges.contiguous() {
+            return Cow::Borrowed(substr(field, range));
         }
+        return Cow::Owned(
+            field
+                .chars()
+                .enumerate()
+                .filter(|(n, _)| filter_by_ranges(ranges, *n as i64))
+                .map(|(_, c)| c)
+                .collect(),
+        );
     }
+    let char_range = normalize_range(&grid_slice.character, field.chars().count());
+    if char_range.step == 1 && !grid_slice.character.exclude {
+        return Cow::Borrowed(substr(field, &char_range));
+    }
+    Cow::Owned(if char_range.step > 0 {
+        slice_chars_from_iter(grid_slice, char_range, field.chars())
+    } else {
+        slice_chars_from_iter(grid_slice, char_range, field.chars().rev())
+    })
+}
 
-    fn slice_chars_from_iter<I: Iterator<Item = char>>(
-        &self,
-        char_range: grid_slice_parser::GridSliceRange,
-        it: I,
-    ) -> String {
-        it.enumerate()
-            .filter(|(n, _)| {
-                self.filter_by_range(&char_range, *n as i64, self.grid_slice.character.exclude)
-            })
-            .map(|(_, c)| c)
-            .collect()
+// Returns the characters of a normalized range with step 1.
+fn substr<'a>(field: &'a str, range: &grid_slice_parser::GridSliceRange) -> &'a str {
+    let byte_offset = |n: i64| {
+        field
+            .char_indices()
+            .nth(n as usize)
+            .map_or(field.len(), |(i, _)| i)
+    };
+    let from = byte_offset(range.from);
+    let to = byte_offset(range.to + 1);
+    if from < to {
+        &field[from..to]
+    } else {
+        ""
     }
+}
 
-    fn filter_by_range(
-        &self,
-        range: &grid_slice_parser::GridSliceRange,
-        current: i64,
-        exclude: bool,
-    ) -> bool {
-        if exclude {
-            !is_inside_range(range, current)
-        } else {
-            is_inside_range(range, current)
+fn slice_chars_from_iter<I: Iterator<Item = char>>(
+    grid_slice: &grid_slice_parser::GridSliceFilter,
+    char_range: grid_slice_parser::GridSliceRange,
+    it: I,
+) -> String {
+    it.enumerate()
+        .filter(|(n, _)| filter_by_range(&char_range, *n as i64, grid_slice.character.exclude))
+        .map(|(_, c)| c)
+        .collect()
+}
+
+pub fn filter_by_range(
+    range: &grid_slice_parser::GridSliceRange,
+    current: i64,
+    exclude: bool,
+) -> bool {
+    if exclude {
+        !is_inside_range(range, current)
+    } else {
+        is_inside_range(range, current)
+    }
+}
+
+// Ranges of a range set normalized for a given number of items. Both lists are sorted
+// by `from` and the overlapping or adjacent ranges with step 1 are merged, so usually
+// only a couple of ranges have to be checked for each item.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MergedRanges {
+    include: Vec<grid_slice_parser::GridSliceRange>,
+    exclude: Vec<grid_slice_parser::GridSliceRange>,
+    // The set has only ranges with "!", so everything else is selected.
+    include_all: bool,
+}
+
+impl MergedRanges {
+    // Returns the only range if the selected items are not interrupted.
+    fn contiguous(&self) -> Option<&grid_slice_parser::GridSliceRange> {
+        match self.include.as_slice() {
+            [range] if range.step == 1 && self.exclude.is_empty() => Some(range),
+            _ => None,
         }
     }
 }
 
-impl<I: Iterator<Item = Vec<String>>> Iterator for GridSlice<I> {
-    type Item = Vec<String>;
+pub fn merge_ranges(set: &grid_slice_parser::RangeSet, length: usize) -> MergedRanges {
+    let merge = |exclude: bool| {
+        let mut ranges: Vec<grid_slice_parser::GridSliceRange> = set
+            .ranges
+            .iter()
+            .filter(|r| r.exclude == exclude)
+            .map(|r| normalize_range(r, length))
+            .filter(|r| r.from <= r.to)
+            .collect();
+        ranges.sort_by_key(|r| r.from);
+        let mut merged: Vec<grid_slice_parser::GridSliceRange> = Vec::new();
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if last.step == 1 && range.step == 1 && range.from <= last.to + 1 => {
+                    last.to = last.to.max(range.to);
+                }
+                _ => merged.push(range),
+            }
+        }
+        merged
+    };
+    MergedRanges {
+        include: merge(false),
+        exclude: merge(true),
+        include_all: set.ranges.iter().all(|r| r.exclude),
+    }
+}
+
+pub fn filter_by_ranges(ranges: &MergedRanges, current: i64) -> bool {
+    let inside = |list: &[grid_slice_parser::GridSliceRange]| {
+        list.iter()
+            .take_while(|r| r.from <= current)
+            .any(|r| is_inside_range(r, current))
+    };
+    (ranges.include_all || inside(&ranges.include)) && !inside(&ranges.exclude)
+}
+
+impl<'a, I: Iterator<Item = Vec<Cell<'a>>>> Iterator for GridSlice<'a, I> {
+    type Item = Line<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
         loop {
-            let fields: Vec<String> = match self.source {
-                GridSliceSource::Iter(ref mut i) => i.next()?,
-                GridSliceSource::SavedLines(ref l) => l.get(self.num_line)?.to_vec(),
+            let (fields, number) = match self.source {
+                GridSliceSource::Iter(ref mut i) => (i.next()?, self.num_line),
+                GridSliceSource::SavedLines(ref mut l) => {
+                    let fields = l.next()?;
+                    // Saved lines are reversed for negative steps, so the number of lines
+                    // left is the original line number.
+                    let number = if self.grid_slice.line.step < 0 {
+                        l.len()
+                    } else {
+                        self.num_line
+                    };
+                    (fields, number)
+                }
             };
             self.num_line += 1;
-            if self.filter_by_range(
-                &self.grid_slice.line,
-                self.num_line as i64 - 1,
-                self.grid_slice.line.exclude,
-            ) {
-                return Some(self.slice_fields(fields));
+            if number == 0 {
+                if let Some(ref mut f) = self.filter {
+                    f.set_header(&fields);
+                }
+            }
+            let current = self.num_line as i64 - 1;
+            let selected = match self.line_ranges {
+                Some(ref ranges) => filter_by_ranges(ranges, current),
+                None => {
+                    filter_by_range(&self.grid_slice.line, current, self.grid_slice.line.exclude)
+                }
+            };
+            if selected && self.filter.as_ref().is_none_or(|f| f.matches(&fields)) {
+                let cells = slice_fields(&self.grid_slice, &fields, &mut self.cache);
+                return Some(Line {
+                    number,
+                    fields,
+                    cells,
+                    selected: true,
+                });
+            } else if self.skipped_lines {
+                return Some(Line {
+                    number,
+                    fields,
+                    cells: Vec::new(),
+                    selected: false,
+                });
             }
         }
     }
 }
 
-pub fn grid_slice_iter<I: Iterator<Item = Vec<String>>>(
+pub fn grid_slice_iter<'a, I: Iterator<Item = Vec<Cell<'a>>>>(
     mut grid_slice: grid_slice_parser::GridSliceFilter,
     iter: I,
-) -> GridSlice<I> {
-    let post_process: bool =
-        grid_slice.line.from < 0 || grid_slice.line.to < -1 || grid_slice.line.step < 0;
-
-    if post_process {
-        let mut lines: Vec<Vec<String>> = iter.collect();
+) -> GridSlice<'a, I> {
+    if lines_need_post_process(&grid_slice) {
+        let mut lines: Vec<Vec<Cell<'a>>> = iter.collect();
         grid_slice.line = normalize_range(&grid_slice.line, lines.len());
         if grid_slice.line.step < 0 {
             lines.reverse();
         }
+        let line_ranges = grid_slice
+            .line_set
+            .as_ref()
+            .map(|s| merge_ranges(s, lines.len()));
         GridSlice {
-            grid_slice: grid_slice,
-            source: GridSliceSource::SavedLines(lines),
+            grid_slice,
+            source: GridSliceSource::SavedLines(lines.into_iter()),
             num_line: 0,
+            skipped_lines: false,
+            filter: None,
+            line_ranges,
+            cache: SliceCache::default(),
         }
     } else {
+        let line_ranges = grid_slice
+            .line_set
+            .as_ref()
+            .map(|s| merge_ranges(s, UNKNOWN_LENGTH));
         GridSlice {
-            grid_slice: grid_slice,
+            grid_slice,
             source: GridSliceSource::Iter(iter),
             num_line: 0,
+            skipped_lines: false,
+            filter: None,
+            line_ranges,
+            cache: SliceCache::default(),
         }
     }
 }
 
-fn normalize_range(
+// Ranges that count from the end or go backwards need the whole input to be read first.
+pub fn needs_post_process(range: &grid_slice_parser::GridSliceRange) -> bool {
+    let last = if range.exclusive { 0 } else { -1 };
+    range.from < 0 || range.to < last || range.step < 0
+}
+
+pub fn set_needs_post_process(set: &grid_slice_parser::RangeSet) -> bool {
+    set.ranges.iter().any(needs_post_process)
+}
+
+// Whether the line range or any range of the line set needs the whole input.
+pub fn lines_need_post_process(grid_slice: &grid_slice_parser::GridSliceFilter) -> bool {
+    needs_post_process(&grid_slice.line)
+        || grid_slice
+            .line_set
+            .as_ref()
+            .is_some_and(set_needs_post_process)
+}
+
+// Range sets that don't need post-processing are merged for a stream of unknown length
+// as if it never ended.
+pub const UNKNOWN_LENGTH: usize = isize::MAX as usize;
+
+// Converts a possibly negative index into an offset from the beginning. Returns None
+// if the index is out of bounds.
+pub fn normalize_index(index: i64, length: usize) -> Option<usize> {
+    let index = if index < 0 {
+        length as i64 + index
+    } else {
+        index
+    };
+    if index < 0 || index >= length as i64 {
+        None
+    } else {
+        Some(index as usize)
+    }
+}
+
+// Returns the items in the order they appear in `list` (duplicates included) or, if
+// `exclude` is set, all the items that are not in `list` in their original order.
+fn select_by_list<'a, T>(list: &[i64], exclude: bool, items: &'a [T]) -> Vec<&'a T> {
+    if exclude {
+        items
+            .iter()
+            .enumerate()
+            .filter(|(n, _)| {
+                !list
+                    .iter()
+                    .any(|i| normalize_index(*i, items.len()) == Some(*n))
+            })
+            .map(|(_, item)| item)
+            .collect()
+    } else {
+        list.iter()
+            .filter_map(|i| normalize_index(*i, items.len()))
+            .map(|i| &items[i])
+            .collect()
+    }
+}
+
+// Converts negative indexes into offsets from the beginning and clamps the range to the
+// existing items, the same way Python does. For negative steps the range is mirrored, so
+// it could be applied to the items in reverse order. Normalized ranges are inclusive.
+pub fn normalize_range(
     range: &grid_slice_parser::GridSliceRange,
     length: usize,
 ) -> grid_slice_parser::GridSliceRange {
     let length = length as i64;
 
-    let from = if range.from < 0 {
-        if length < -range.from {
-            length
-        } else {
-            length + range.from
-        }
+    let absolute = |i: i64| if i < 0 { length + i } else { i };
+    let from = absolute(range.from);
+    let to = if range.relative {
+        from.saturating_add(range.to)
     } else {
-        range.from
+        absolute(range.to)
     };
-    let to = if range.to < 0 {
-        if length < -range.to {
-            0
-        } else {
-            length + range.to
-        }
+    // The first and the last item of the range in the input order.
+    let (from, to) = if range.exclusive {
+        exclusive_bounds(from, to, range.step, length)
     } else {
-        range.to
+        (from.max(0), to.min(length - 1))
     };
-    if range.step > 0 {
-        grid_slice_parser::GridSliceRange {
-            from: from,
-            to: to,
-            step: range.step,
-            exclude: range.exclude,
-        }
+    let (from, to) = if from > to {
+        // Nothing is selected. Note that `to` can't be -1 because it means the end.
+        (1, 0)
+    } else if range.step > 0 {
+        (from, to)
     } else {
-        grid_slice_parser::GridSliceRange {
-            from: length - to - 1,
-            to: length - from - 1,
-            step: range.step,
-            exclude: range.exclude,
-        }
+        (length - to - 1, length - from - 1)
+    };
+    grid_slice_parser::GridSliceRange {
+        from,
+        to,
+        step: range.step,
+        exclude: range.exclude,
+        exclusive: false,
+        relative: false,
+    }
+}
+
+// Clamps the endpoints that no longer count from the end the same way Python's
+// `slice.indices()` does and returns the first and the last item of the range. With a
+// negative step `from` is the last one.
+fn exclusive_bounds(from: i64, to: i64, step: i64, length: i64) -> (i64, i64) {
+    let clamp = |i: i64| match (i, step > 0) {
+        (i, true) if i < 0 => 0,
+        (i, false) if i < 0 => -1,
+        (i, true) if i >= length => length,
+        (i, false) if i >= length => length - 1,
+        (i, _) => i,
+    };
+    let (from, to) = (clamp(from), clamp(to));
+    if step > 0 {
+        (from, to - 1)
+    } else {
+        (to + 1, from)
     }
 }
 
 fn is_inside_range(range: &grid_slice_parser::GridSliceRange, current: i64) -> bool {
     (current >= range.from)
-        && (current <= range.to || range.to == -1)
+        && if range.exclusive {
+            current < range.to
+        } else {
+            current <= range.to || range.to == -1
+        }
         && (((range.from - current) % range.step) == 0)
 }
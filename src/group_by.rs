@@ -0,0 +1,117 @@
+use std::collections::HashMap;
+
+use crate::aggregate::{self, Accumulator, NonNumeric, Reducer};
+
+struct Group {
+    key: Vec<String>,
+    lines: usize,
+    numbers: Accumulator,
+    first: Vec<String>,
+    last: Vec<String>,
+}
+
+impl Group {
+    fn value(&self, reducer: Reducer) -> String {
+        match reducer {
+            Reducer::Count => self.lines.to_string(),
+            Reducer::First => self.first.join(" "),
+            Reducer::Last => self.last.join(" "),
+            _ => aggregate::format_number(self.numbers.value(reducer)),
+        }
+    }
+}
+
+pub struct GroupBy {
+    reducers: Vec<Reducer>,
+    non_numeric: NonNumeric,
+    // Groups in the order their keys were first seen.
+    groups: Vec<Group>,
+    index: HashMap<Vec<String>, usize>,
+}
+
+impl GroupBy {
+    pub fn new(reducers: Vec<Reducer>, non_numeric: NonNumeric) -> Self {
+        GroupBy {
+            reducers,
+            non_numeric,
+            groups: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, key: Vec<String>, values: Vec<String>) -> Result<(), &'static str> {
+        let groups = &mut self.groups;
+        let n = *self.index.entry(key).or_insert_with_key(|key| {
+            groups.push(Group {
+                key: key.clone(),
+                lines: 0,
+                numbers: Accumulator::default(),
+                first: values.clone(),
+                last: Vec::new(),
+            });
+            groups.len() - 1
+        });
+        let group = &mut self.groups[n];
+        for value in values.iter() {
+            if let Some(v) = aggregate::parse_number(value, self.non_numeric)? {
+                group.numbers.add(v);
+            }
+        }
+        group.lines += 1;
+        group.last = values;
+        Ok(())
+    }
+
+    // Returns a line per group that starts with the key followed by the value of each
+    // reducer. Groups are returned either in the order of their first appearance or
+    // sorted by their keys.
+    pub fn summary(mut self, sorted: bool) -> Vec<Vec<String>> {
+        if sorted {
+            self.groups.sort_by(|a, b| a.key.cmp(&b.key));
+        }
+        let reducers = self.reducers;
+        self.groups
+            .into_iter()
+            .map(|g| {
+                let values: Vec<String> = reducers.iter().map(|r| g.value(*r)).collect();
+                g.key.into_iter().chain(values).collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn group_by_test() {
+        let reducers = aggregate::parse_reducers("count,sum,max,first,last").unwrap();
+        let mut group_by = GroupBy::new(reducers, NonNumeric::Skip);
+        group_by.add(cells(&["b"]), cells(&["1"])).unwrap();
+        group_by.add(cells(&["a"]), cells(&["x"])).unwrap();
+        group_by.add(cells(&["b"]), cells(&["5"])).unwrap();
+        group_by.add(cells(&["b"]), cells(&["2"])).unwrap();
+        let expected = vec![
+            cells(&["b", "3", "8", "5", "1", "2"]),
+            cells(&["a", "1", "0", "-", "x", "x"]),
+        ];
+        let mut sorted = GroupBy::new(vec![Reducer::Count], NonNumeric::Skip);
+        sorted.add(cells(&["b", "1"]), Vec::new()).unwrap();
+        sorted.add(cells(&["a", "2"]), Vec::new()).unwrap();
+        sorted.add(cells(&["a", "1"]), Vec::new()).unwrap();
+        assert_eq!(group_by.summary(false), expected);
+        assert_eq!(
+            sorted.summary(true),
+            vec![
+                cells(&["a", "1", "1"]),
+                cells(&["a", "2", "1"]),
+                cells(&["b", "1", "1"]),
+            ]
+        );
+    }
+}
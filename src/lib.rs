@@ -0,0 +1,22 @@
+pub mod parser {
+    pub mod grid_slice_parser;
+    pub mod predicate_parser;
+    pub mod template_parser;
+}
+
+pub mod aggregate;
+pub mod context;
+pub mod fast_path;
+pub mod follow;
+pub mod grid_slice;
+pub mod group_by;
+pub mod mmap;
+pub mod paragraph;
+pub mod parallel;
+pub mod pattern;
+pub mod predicate;
+pub mod records;
+pub mod sort;
+pub mod template;
+pub mod transpose;
+pub mod uniq;
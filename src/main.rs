@@ -1,70 +1,624 @@
+use std::cell::Cell;
 use std::env;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Write};
+use std::rc::Rc;
 
-mod parser {
-    pub mod grid_slice_parser;
-}
-
-mod grid_slice;
+use rtr::{
+    aggregate, context, fast_path, follow, grid_slice, group_by, mmap, paragraph, parallel, parser,
+    pattern, predicate, records, sort, template, transpose, uniq,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct Opts {
     program: String,
     file: Option<String>,
+    filter: Option<parser::predicate_parser::Predicate>,
+    paragraph_separator: Option<pattern::Pattern>,
+    // Separates records both in the input and in the output. A newline if not set.
+    record_separator: Option<Vec<u8>>,
+    // Print records with CRLF line endings if the input uses them.
+    keep_line_endings: bool,
+    // Keep reading the file as it grows.
+    follow: bool,
+    // The number of threads to process mapped files with. All the cores if not set.
+    threads: Option<usize>,
+    // Read files as streams, so they can be truncated while they are read.
+    no_mmap: bool,
+    // Print the slice in its shortest form or a description of it instead of slicing.
+    normalize: bool,
+    explain: bool,
+    // Python-like ranges where `to` is excluded.
+    exclusive: bool,
+    format: Option<String>,
+    line_numbers: bool,
+    before: usize,
+    after: usize,
+    transpose: bool,
+    filler: String,
+    aggregate: Option<Vec<aggregate::Reducer>>,
+    per_column: bool,
+    non_numeric: aggregate::NonNumeric,
+    group_by: Option<parser::grid_slice_parser::GridSliceFilter>,
+    sort_groups: bool,
+    sort: Option<parser::grid_slice_parser::GridSliceFilter>,
+    sort_options: sort::SortOptions,
+    uniq: Option<parser::grid_slice_parser::GridSliceFilter>,
+    uniq_options: uniq::UniqOptions,
 }
 
-fn parse_args() -> Result<Opts, &'static str> {
-    let mut args = env::args().skip(1);
+// Returns the value of the option `name` if `arg` is that option. The value could be
+// either attached to the option (`--format=...`, `-A3`) or passed as the next argument.
+fn parse_option_value<I: Iterator<Item = String>>(
+    name: &str,
+    arg: &str,
+    args: &mut I,
+) -> Result<Option<String>, &'static str> {
+    if arg == name {
+        return args.next().map(Some).ok_or("Missing option value");
+    }
+    let value = if name.starts_with("--") {
+        arg.strip_prefix(name).and_then(|v| v.strip_prefix('='))
+    } else {
+        arg.strip_prefix(name)
+    };
+    Ok(value.map(|v| v.to_string()))
+}
 
-    Ok(Opts {
-        program: args.next().ok_or("Wrong number of arguments")?,
-        file: args.next(),
-    })
+fn parse_count(value: &str) -> Result<usize, &'static str> {
+    value.parse().or(Err("Invalid number of context lines"))
 }
 
-struct SplitLines<I: BufRead> {
-    source: I,
+// Supports `\0`, `\n`, `\r`, `\t`, `\\` and `\xHH` escapes. `\xHH` is a single byte, so
+// separators don't have to be valid UTF-8.
+fn parse_record_separator(value: &str) -> Result<Vec<u8>, &'static str> {
+    let mut separator = Vec::new();
+    let mut it = value.chars();
+    while let Some(c) = it.next() {
+        if c != '\\' {
+            separator.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+            continue;
+        }
+        let b = match it.next() {
+            Some('0') => b'\0',
+            Some('n') => b'\n',
+            Some('r') => b'\r',
+            Some('t') => b'\t',
+            Some('\\') => b'\\',
+            Some('x') => {
+                let hex: String = it.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(b) if hex.len() == 2 => b,
+                    _ => return Err("Invalid escape sequence in the record separator"),
+                }
+            }
+            _ => return Err("Invalid escape sequence in the record separator"),
+        };
+        separator.push(b);
+    }
+    if separator.is_empty() {
+        return Err("Record separator cannot be empty");
+    }
+    Ok(separator)
 }
 
-impl<I: BufRead> SplitLines<I> {
-    fn new(source: I) -> Self {
-        SplitLines { source: source }
+fn parse_slice(
+    text: &str,
+    exclusive: bool,
+) -> Result<parser::grid_slice_parser::GridSliceFilter, &'static str> {
+    if exclusive {
+        parser::grid_slice_parser::parse_exclusive_grid_slice(text)
+    } else {
+        parser::grid_slice_parser::parse_grid_slice(text)
     }
 }
 
-impl<I: BufRead> Iterator for SplitLines<I> {
-    type Item = Vec<String>;
+fn parse_args() -> Result<Opts, &'static str> {
+    let mut args = env::args().skip(1);
+    let mut opts = Opts {
+        filler: "-".to_string(),
+        ..Opts::default()
+    };
+    let mut program = None;
+    // Slices are parsed at the end, since --exclusive could follow them.
+    let (mut group_by, mut sort, mut uniq) = (None, None, None);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let mut line = String::new();
-        if self.source.read_line(&mut line).ok()? == 0 {
-            None
+    while let Some(arg) = args.next() {
+        if let Some(v) = parse_option_value("--where", &arg, &mut args)? {
+            opts.filter = Some(parser::predicate_parser::parse_predicate(&v)?);
+        } else if let Some(v) = parse_option_value("--paragraph-separator", &arg, &mut args)? {
+            opts.paragraph_separator = Some(pattern::Pattern::new(&v)?);
+        } else if let Some(v) = parse_option_value("--format", &arg, &mut args)? {
+            opts.format = Some(v);
+        } else if arg == "-z" || arg == "--null" {
+            opts.record_separator = Some(vec![b'\0']);
+        } else if let Some(v) = parse_option_value("--record-separator", &arg, &mut args)? {
+            opts.record_separator = Some(parse_record_separator(&v)?);
+        } else if let Some(v) = parse_option_value("--threads", &arg, &mut args)? {
+            opts.threads = match v.parse() {
+                Ok(n) if n > 0 => Some(n),
+                _ => return Err("Invalid number of threads"),
+            };
+        } else if arg == "--normalize" {
+            opts.normalize = true;
+        } else if arg == "--explain" {
+            opts.explain = true;
+        } else if arg == "--exclusive" {
+            opts.exclusive = true;
+        } else if arg == "--no-mmap" {
+            opts.no_mmap = true;
+        } else if arg == "--follow" {
+            opts.follow = true;
+        } else if arg == "--keep-line-endings" {
+            opts.keep_line_endings = true;
+        } else if arg == "-n" {
+            opts.line_numbers = true;
+        } else if let Some(v) = parse_option_value("-A", &arg, &mut args)? {
+            opts.after = parse_count(&v)?;
+        } else if let Some(v) = parse_option_value("-B", &arg, &mut args)? {
+            opts.before = parse_count(&v)?;
+        } else if let Some(v) = parse_option_value("-C", &arg, &mut args)? {
+            opts.before = parse_count(&v)?;
+            opts.after = opts.before;
+        } else if arg == "--transpose" {
+            opts.transpose = true;
+        } else if let Some(v) = parse_option_value("--filler", &arg, &mut args)? {
+            // Fillers have to survive splitting the output into words again.
+            if v.is_empty() || v.contains(char::is_whitespace) {
+                return Err("--filler cannot be empty or contain whitespace");
+            }
+            opts.filler = v;
+        } else if let Some(v) = parse_option_value("--aggregate", &arg, &mut args)? {
+            opts.aggregate = Some(aggregate::parse_reducers(&v)?);
+        } else if arg == "--per-column" {
+            opts.per_column = true;
+        } else if let Some(v) = parse_option_value("--non-numeric", &arg, &mut args)? {
+            opts.non_numeric = aggregate::parse_non_numeric(&v)?;
+        } else if let Some(v) = parse_option_value("--group-by", &arg, &mut args)? {
+            group_by = Some(v);
+        } else if arg == "--sort-groups" {
+            opts.sort_groups = true;
+        } else if let Some(v) = parse_option_value("--sort", &arg, &mut args)? {
+            sort = Some(v);
+        } else if let Some(v) = parse_option_value("--sort-mode", &arg, &mut args)? {
+            opts.sort_options.mode = sort::parse_sort_mode(&v)?;
+        } else if arg == "--reverse" {
+            opts.sort_options.reverse = true;
+        } else if arg == "--stable" {
+            opts.sort_options.stable = true;
+        } else if arg == "--unique" {
+            opts.sort_options.unique = true;
+        } else if let Some(v) = parse_option_value("--uniq", &arg, &mut args)? {
+            uniq = Some(v);
+        } else if arg == "--adjacent" {
+            opts.uniq_options.adjacent = true;
+        } else if arg == "--count" {
+            opts.uniq_options.count = true;
+        } else if program.is_none() {
+            program = Some(arg);
+        } else if opts.file.is_none() {
+            opts.file = Some(arg);
         } else {
-            Some(line.split_whitespace().map(|f| f.to_string()).collect())
+            return Err("Wrong number of arguments");
+        }
+    }
+
+    opts.program = program.ok_or("Wrong number of arguments")?;
+    let exclusive = opts.exclusive;
+    let parse = |slice: Option<String>| match slice {
+        Some(s) => parse_slice(&s, exclusive).map(Some),
+        None => Ok(None),
+    };
+    opts.group_by = parse(group_by)?;
+    opts.sort = parse(sort)?;
+    opts.uniq = parse(uniq)?;
+    let stages = opts.transpose
+        || opts.aggregate.is_some()
+        || opts.group_by.is_some()
+        || opts.sort.is_some()
+        || opts.uniq.is_some();
+    if stages && (opts.before > 0 || opts.after > 0) {
+        return Err(
+            "Context lines cannot be used with --transpose, --sort, --uniq, --group-by or --aggregate",
+        );
+    }
+    if opts.follow {
+        if opts.file.is_none() {
+            return Err("--follow can be used only with a file");
+        }
+        if opts.transpose
+            || opts.aggregate.is_some()
+            || opts.group_by.is_some()
+            || opts.sort.is_some()
+            || (opts.uniq_options.count && !opts.uniq_options.adjacent)
+        {
+            return Err(
+                "--follow cannot be used with --transpose, --sort, --group-by, --aggregate or --uniq --count without --adjacent",
+            );
         }
     }
+    if opts.per_column && opts.group_by.is_some() {
+        return Err("--per-column cannot be used with --group-by");
+    }
+    Ok(opts)
 }
 
-fn main() -> Result<(), &'static str> {
-    let opts = parse_args()?;
-    let grid_slice = parser::grid_slice_parser::parse_grid_slice(&opts.program)?;
-    match opts.file {
-        Some(f) => {
-            let file = File::open(f).or(Err("Unable to open a file"))?;
-            let reader = BufReader::new(file);
-
-            for line in grid_slice::grid_slice_iter(grid_slice, SplitLines::new(reader)) {
-                println!("{}", line.join(" "));
-            }
+// Writing to a closed pipe (e.g. `rtr ... | head`) stops the processing, but it's not
+// reported as an error.
+const BROKEN_PIPE: &str = "Broken pipe";
+
+fn output_error(e: io::Error) -> &'static str {
+    if e.kind() == io::ErrorKind::BrokenPipe {
+        BROKEN_PIPE
+    } else {
+        "Unable to write the output"
+    }
+}
+
+struct Printer<'a> {
+    template: Option<parser::template_parser::Template>,
+    file_name: &'a str,
+    line_numbers: bool,
+    record_separator: &'a [u8],
+    out: Box<dyn Write + 'a>,
+    // Flush the output after each record instead of when the buffer is full.
+    flush: bool,
+}
+
+impl Printer<'_> {
+    fn print_selected(&mut self, line: &grid_slice::Line) -> Result<(), &'static str> {
+        let text = match self.template {
+            Some(ref t) => template::render(t, line, self.file_name),
+            None => line.cells.join(" "),
+        };
+        self.print(line.number, ':', &text)
+    }
+
+    fn print_context(&mut self, line: &grid_slice::Line) -> Result<(), &'static str> {
+        self.print(line.number, '-', &line.fields.join(" "))
+    }
+
+    // Line numbers are followed by ':' for selected lines and by '-' for context lines
+    // the same way grep does it.
+    fn print(&mut self, number: usize, delimiter: char, text: &str) -> Result<(), &'static str> {
+        if self.line_numbers {
+            write!(self.out, "{}{}", number, delimiter).map_err(output_error)?;
         }
-        None => {
-            let stdin = io::stdin();
-            for line in grid_slice::grid_slice_iter(grid_slice, SplitLines::new(stdin.lock())) {
-                println!("{}", line.join(" "));
+        self.print_record(text)
+    }
+
+    fn print_record(&mut self, text: &str) -> Result<(), &'static str> {
+        self.out
+            .write_all(text.as_bytes())
+            .and_then(|_| self.out.write_all(self.record_separator))
+            .map_err(output_error)?;
+        if self.flush {
+            self.out.flush().map_err(output_error)?;
+        }
+        Ok(())
+    }
+}
+
+fn run<'a, I: Iterator<Item = Vec<grid_slice::Cell<'a>>> + 'a>(
+    opts: &'a Opts,
+    grid_slice: parser::grid_slice_parser::GridSliceFilter,
+    filter: Option<predicate::LineFilter>,
+    printer: &mut Printer,
+    records: I,
+) -> Result<(), &'static str> {
+    if grid_slice.paragraph.is_some() {
+        if opts.before > 0 || opts.after > 0 {
+            return Err("Context lines cannot be used with paragraphs");
+        }
+        let lines = paragraph::paragraph_iter(
+            grid_slice,
+            filter,
+            opts.paragraph_separator.clone(),
+            records,
+        );
+        return run_stages(opts, printer, lines);
+    }
+    let mut lines = grid_slice::grid_slice_iter(grid_slice, records);
+    if let Some(f) = filter {
+        lines = lines.with_filter(f);
+    }
+    if opts.before > 0 || opts.after > 0 {
+        let lines = context::context_iter(lines.with_skipped_lines(), opts.before, opts.after);
+        for line in lines {
+            match line {
+                context::ContextLine::Selected(l) => printer.print_selected(&l)?,
+                context::ContextLine::Context(l) => printer.print_context(&l)?,
+                context::ContextLine::Separator => printer.print_record("--")?,
             }
         }
+        Ok(())
+    } else {
+        run_stages(opts, printer, Box::new(lines))
     }
+}
 
+fn run_stages<'a>(
+    opts: &'a Opts,
+    printer: &mut Printer,
+    mut lines: Box<dyn Iterator<Item = grid_slice::Line<'a>> + 'a>,
+) -> Result<(), &'static str> {
+    if opts.transpose {
+        lines = Box::new(transpose::transpose(lines, &opts.filler).into_iter());
+    }
+    if let Some(ref key) = opts.sort {
+        lines = Box::new(sort::sort(lines, key, &opts.sort_options).into_iter());
+    }
+    if let Some(ref key) = opts.uniq {
+        lines = uniq::uniq(lines, key, &opts.uniq_options);
+    }
+    if let Some(ref key) = opts.group_by {
+        let reducers = opts
+            .aggregate
+            .clone()
+            .unwrap_or_else(|| vec![aggregate::Reducer::Count]);
+        let mut group_by = group_by::GroupBy::new(reducers, opts.non_numeric);
+        let mut cache = grid_slice::SliceCache::default();
+        for line in lines {
+            let key = grid_slice::slice_fields(key, &line.fields, &mut cache);
+            group_by.add(
+                key.into_iter().map(|c| c.into_owned()).collect(),
+                line.cells.into_iter().map(|c| c.into_owned()).collect(),
+            )?;
+        }
+        for summary in group_by.summary(opts.sort_groups) {
+            printer.print_record(&summary.join(" "))?;
+        }
+    } else if let Some(ref reducers) = opts.aggregate {
+        let mut aggregation =
+            aggregate::Aggregation::new(reducers.clone(), opts.per_column, opts.non_numeric);
+        for line in lines {
+            aggregation.add(&line.cells)?;
+        }
+        for summary in aggregation.summary() {
+            printer.print_record(&summary.join(" "))?;
+        }
+    } else {
+        for line in lines {
+            printer.print_selected(&line)?;
+        }
+    }
     Ok(())
 }
+
+// Checks whether the first line of the input ends with CRLF. Only the buffered part of
+// the input is looked at, so the input is treated as having LF line endings if the
+// first line doesn't fit into the buffer.
+fn detect_crlf<R: BufRead>(reader: &mut R) -> bool {
+    match reader.fill_buf() {
+        Ok(buf) => buf
+            .iter()
+            .position(|b| *b == b'\n')
+            .is_some_and(|n| n > 0 && buf[n - 1] == b'\r'),
+        Err(_) => false,
+    }
+}
+
+// The end of a followed file is never reached, so only `l-N` (start with the last N
+// lines) could count from the end. It's replaced with `l0` and the number of lines to
+// start with is returned.
+fn follow_start(
+    grid_slice: &mut parser::grid_slice_parser::GridSliceFilter,
+) -> Result<Option<usize>, &'static str> {
+    let error = "Only positive line indexes and l-N could be used with --follow";
+    if grid_slice
+        .paragraph
+        .as_ref()
+        .is_some_and(grid_slice::needs_post_process)
+        || grid_slice
+            .paragraph_set
+            .as_ref()
+            .is_some_and(grid_slice::set_needs_post_process)
+    {
+        return Err(error);
+    }
+    // Line sets are not rewritten, so they can't count from the end at all.
+    if grid_slice.line_set.is_some() {
+        if grid_slice::lines_need_post_process(grid_slice) {
+            return Err(error);
+        }
+        return Ok(None);
+    }
+    let line = &mut grid_slice.line;
+    let to_end = if line.exclusive {
+        line.to == i64::MAX
+    } else {
+        line.to == -1
+    };
+    if line.from < 0 && to_end && line.step > 0 && !line.exclude {
+        let last = line.from.unsigned_abs() as usize;
+        line.from = 0;
+        return Ok(Some(last));
+    }
+    if grid_slice::needs_post_process(line) {
+        return Err(error);
+    }
+    Ok(None)
+}
+
+// Most of the options need whole lines, so only the plain slicing of a stream could
+// be done without copying the fields. Followed files are not buffered to print new
+// lines as soon as they appear.
+fn use_fast_path(
+    opts: &Opts,
+    grid_slice: &parser::grid_slice_parser::GridSliceFilter,
+    filter: &Option<predicate::LineFilter>,
+) -> bool {
+    grid_slice.paragraph.is_none()
+        && !grid_slice::lines_need_post_process(grid_slice)
+        && filter.is_none()
+        && opts.format.is_none()
+        && opts.before == 0
+        && opts.after == 0
+        && !opts.follow
+        && !opts.transpose
+        && opts.aggregate.is_none()
+        && opts.group_by.is_none()
+        && opts.sort.is_none()
+        && opts.uniq.is_none()
+}
+
+fn run_fast_path<R: BufRead>(
+    grid_slice: &parser::grid_slice_parser::GridSliceFilter,
+    separator: &[u8],
+    printer: &mut Printer,
+    reader: R,
+) -> Result<(), &'static str> {
+    fast_path::FastPath::new(
+        grid_slice,
+        separator,
+        printer.record_separator,
+        printer.line_numbers,
+    )
+    .run(reader, 0, &mut printer.out)
+    .map_err(output_error)
+}
+
+// Regular files are mapped into memory, so their fields don't have to be copied.
+enum Input<'a> {
+    Mapped(mmap::Mmap),
+    Reader(Box<dyn BufRead + 'a>),
+}
+
+fn main() -> Result<(), &'static str> {
+    let mut opts = parse_args()?;
+    let mut grid_slice = parse_slice(&opts.program, opts.exclusive)?;
+    if opts.normalize {
+        println!("{}", grid_slice);
+        return Ok(());
+    }
+    if opts.explain {
+        print!("{}", grid_slice.explain());
+        return Ok(());
+    }
+    let filter = opts.filter.take().map(predicate::LineFilter::new);
+    let template = match opts.format {
+        Some(ref f) => Some(parser::template_parser::parse_template(f)?),
+        None => None,
+    };
+    let separator = opts.record_separator.as_deref().unwrap_or(b"\n");
+    let stdin = io::stdin();
+    let mut input = match opts.file {
+        Some(ref f) if opts.follow => {
+            let last = follow_start(&mut grid_slice)?;
+            Input::Reader(Box::new(BufReader::new(
+                follow::Follow::open(f, separator, last).or(Err("Unable to open a file"))?,
+            )))
+        }
+        Some(ref f) if opts.no_mmap => Input::Reader(Box::new(BufReader::new(
+            File::open(f).or(Err("Unable to open a file"))?,
+        ))),
+        Some(ref f) => match mmap::Mmap::open(f).or(Err("Unable to open a file"))? {
+            Some(map) => Input::Mapped(map),
+            None => Input::Reader(Box::new(BufReader::new(
+                File::open(f).or(Err("Unable to open a file"))?,
+            ))),
+        },
+        None => Input::Reader(Box::new(stdin.lock())),
+    };
+    let read_failed = Rc::new(Cell::new(false));
+    input = match input {
+        Input::Reader(reader) => Input::Reader(Box::new(records::CheckedReader::new(
+            reader,
+            read_failed.clone(),
+        ))),
+        mapped => mapped,
+    };
+    let crlf = opts.keep_line_endings
+        && match input {
+            Input::Mapped(ref map) => detect_crlf(&mut &map[..]),
+            Input::Reader(ref mut reader) => detect_crlf(reader),
+        };
+    let record_separator = match opts.record_separator {
+        Some(ref s) => s.as_slice(),
+        None if crlf => b"\r\n",
+        None => b"\n",
+    };
+    let threads = opts
+        .threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()));
+    let stdout = io::stdout();
+    let mut printer = Printer {
+        template,
+        file_name: opts.file.as_deref().unwrap_or("-"),
+        line_numbers: opts.line_numbers,
+        record_separator,
+        out: Box::new(io::BufWriter::new(stdout.lock())),
+        flush: opts.follow,
+    };
+    let result = if use_fast_path(&opts, &grid_slice, &filter) {
+        match input {
+            Input::Mapped(ref map) if separator.len() == 1 && threads > 1 => parallel::run(
+                &grid_slice,
+                map,
+                separator,
+                printer.record_separator,
+                printer.line_numbers,
+                threads,
+                &mut printer.out,
+            )
+            .map_err(output_error),
+            Input::Mapped(ref map) => run_fast_path(&grid_slice, separator, &mut printer, &map[..]),
+            Input::Reader(reader) => run_fast_path(&grid_slice, separator, &mut printer, reader),
+        }
+    } else {
+        match input {
+            Input::Mapped(ref map) => run(
+                &opts,
+                grid_slice,
+                filter,
+                &mut printer,
+                mmap::MmapLines::new(map, separator),
+            ),
+            Input::Reader(reader) => run(
+                &opts,
+                grid_slice,
+                filter,
+                &mut printer,
+                records::SplitLines::new(reader, separator),
+            ),
+        }
+    };
+    let result = result.and_then(|_| printer.out.flush().map_err(output_error));
+    // The fast path can't tell read errors from write errors, so they are checked
+    // first. Mapped files don't fail to read.
+    if read_failed.get() {
+        let _ = printer.out.flush();
+        return Err("Unable to read the input");
+    }
+    match result {
+        Err(BROKEN_PIPE) => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_crlf_test() {
+        assert!(detect_crlf(&mut "a b\r\nc\n".as_bytes()));
+        assert!(!detect_crlf(&mut "a b\nc\r\n".as_bytes()));
+        assert!(!detect_crlf(&mut "\na\r\n".as_bytes()));
+        assert!(!detect_crlf(&mut "".as_bytes()));
+    }
+
+    #[test]
+    fn parse_record_separator_test() {
+        assert_eq!(parse_record_separator("\\0"), Ok(b"\0".to_vec()));
+        assert_eq!(parse_record_separator(";\\t\\x1e"), Ok(b";\t\x1e".to_vec()));
+        assert_eq!(
+            parse_record_separator("é\\xff\\x80"),
+            Ok(b"\xc3\xa9\xff\x80".to_vec())
+        );
+        assert!(parse_record_separator("").is_err());
+        assert!(parse_record_separator("\\q").is_err());
+        assert!(parse_record_separator("\\x4").is_err());
+        assert!(parse_record_separator("\\xg0").is_err());
+    }
+}
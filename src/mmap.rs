@@ -0,0 +1,185 @@
+use std::fs::File;
+use std::io;
+use std::ops::Deref;
+
+use crate::grid_slice::Cell;
+
+#[cfg(unix)]
+mod sys {
+    use std::os::raw::{c_int, c_long, c_void};
+
+    pub const PROT_READ: c_int = 1;
+    pub const MAP_PRIVATE: c_int = 2;
+
+    extern "C" {
+        pub fn mmap(
+            addr: *mut c_void,
+            len: usize,
+            prot: c_int,
+            flags: c_int,
+            fd: c_int,
+            offset: c_long,
+        ) -> *mut c_void;
+        pub fn munmap(addr: *mut c_void, len: usize) -> c_int;
+    }
+}
+
+// A read-only memory mapping of a whole file. The file must not be truncated while it's
+// mapped: reading the missing pages would crash the process.
+pub struct Mmap {
+    ptr: *const u8,
+    len: usize,
+}
+
+impl Mmap {
+    // Returns None for files that can't be mapped (pipes, empty files and files like the
+    // ones in /proc that report zero length), so they have to be read as usual.
+    pub fn open(path: &str) -> io::Result<Option<Self>> {
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+        if !metadata.is_file() || metadata.len() == 0 || metadata.len() > usize::MAX as u64 {
+            return Ok(None);
+        }
+        Ok(Self::map(&file, metadata.len() as usize))
+    }
+
+    #[cfg(unix)]
+    fn map(file: &File, len: usize) -> Option<Self> {
+        use std::os::unix::io::AsRawFd;
+        let ptr = unsafe {
+            sys::mmap(
+                std::ptr::null_mut(),
+                len,
+                sys::PROT_READ,
+                sys::MAP_PRIVATE,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        // MAP_FAILED
+        if ptr as isize == -1 {
+            return None;
+        }
+        Some(Mmap {
+            ptr: ptr as *const u8,
+            len,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn map(_file: &File, _len: usize) -> Option<Self> {
+        None
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        unsafe {
+            sys::munmap(self.ptr as *mut _, self.len);
+        }
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    if let [b] = needle {
+        haystack.iter().position(|c| c == b)
+    } else {
+        haystack.windows(needle.len()).position(|w| w == needle)
+    }
+}
+
+// Splits mapped input into records the same way `SplitLines` does, but fields borrow
+// from the input instead of being copied. Only records with invalid UTF-8 are copied.
+pub struct MmapLines<'a> {
+    data: &'a [u8],
+    separator: &'a [u8],
+}
+
+impl<'a> MmapLines<'a> {
+    pub fn new(data: &'a [u8], separator: &'a [u8]) -> Self {
+        MmapLines { data, separator }
+    }
+}
+
+impl<'a> Iterator for MmapLines<'a> {
+    type Item = Vec<Cell<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let mut record = match find(self.data, self.separator) {
+            Some(n) => {
+                let record = &self.data[..n];
+                self.data = &self.data[n + self.separator.len()..];
+                record
+            }
+            None => std::mem::take(&mut self.data),
+        };
+        if self.separator == b"\n" {
+            if let Some(r) = record.strip_suffix(b"\r") {
+                record = r;
+            }
+        }
+        Some(match std::str::from_utf8(record) {
+            Ok(s) => s.split_whitespace().map(Cell::Borrowed).collect(),
+            Err(_) => String::from_utf8_lossy(record)
+                .split_whitespace()
+                .map(|f| Cell::Owned(f.to_string()))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn split(input: &str, separator: &str) -> Vec<Vec<String>> {
+        MmapLines::new(input.as_bytes(), separator.as_bytes())
+            .map(|l| l.into_iter().map(Cell::into_owned).collect())
+            .collect()
+    }
+
+    #[test]
+    fn mmap_lines_test() {
+        assert_eq!(split("a b\nc\n", "\n"), vec![vec!["a", "b"], vec!["c"]]);
+        assert_eq!(
+            split("a b\0c d\ne", "\0"),
+            vec![vec!["a", "b"], vec!["c", "d", "e"]]
+        );
+        assert_eq!(split("a;b;;c;d;;", ";;"), vec![vec!["a;b"], vec!["c;d"]]);
+        assert!(split("", "\n").is_empty());
+        assert_eq!(
+            split("a \r\n\nb\r\n", "\n"),
+            vec![vec!["a"], vec![], vec!["b"]]
+        );
+        let lines: Vec<_> = MmapLines::new(b"a\xffb c\n", b"\n").collect();
+        assert_eq!(lines, vec![vec!["a\u{fffd}b", "c"]]);
+        assert!(matches!(lines[0][0], Cell::Owned(_)));
+    }
+
+    #[test]
+    fn mmap_test() {
+        let path = std::env::temp_dir().join(format!("rtr-mmap-{}", std::process::id()));
+        let path = path.to_str().unwrap();
+        fs::write(path, "a b\nc\n").unwrap();
+        let map = Mmap::open(path).unwrap();
+        if cfg!(unix) {
+            assert_eq!(&map.unwrap()[..], b"a b\nc\n");
+        }
+        fs::write(path, "").unwrap();
+        assert!(Mmap::open(path).unwrap().is_none());
+        fs::remove_file(path).unwrap();
+    }
+}
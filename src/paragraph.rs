@@ -0,0 +1,147 @@
+use crate::grid_slice::{self, Cell, Line, MergedRanges};
+use crate::parser::grid_slice_parser::{GridSliceFilter, GridSliceRange};
+use crate::pattern::Pattern;
+use crate::predicate::LineFilter;
+
+// A group of consecutive lines that are not separators.
+struct Record<'a> {
+    // Index of the first line of the record in the input.
+    start: usize,
+    lines: Vec<Vec<Cell<'a>>>,
+}
+
+struct Records<I> {
+    source: I,
+    // Lines matching the separator delimit records. Empty lines are used if not set.
+    separator: Option<Pattern>,
+    num_line: usize,
+}
+
+impl<I> Records<I> {
+    fn is_separator(&self, fields: &[Cell]) -> bool {
+        match self.separator {
+            Some(ref p) => p.is_match(&fields.join(" ")),
+            None => fields.is_empty(),
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = Vec<Cell<'a>>>> Iterator for Records<I> {
+    type Item = Record<'a>;
+
+    // Several separators in a row don't produce empty records.
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = Record {
+            start: self.num_line,
+            lines: Vec::new(),
+        };
+        while let Some(fields) = self.source.next() {
+            self.num_line += 1;
+            if !self.is_separator(&fields) {
+                record.lines.push(fields);
+            } else if record.lines.is_empty() {
+                record.start = self.num_line;
+            } else {
+                return Some(record);
+            }
+        }
+        if record.lines.is_empty() {
+            None
+        } else {
+            Some(record)
+        }
+    }
+}
+
+// Selects records by the paragraph range or set of `grid_slice` and then lines, fields and
+// characters within each of them. Lines keep their numbers in the whole input and field
+// names of `filter` are resolved against the first line of each record.
+pub fn paragraph_iter<'a, I: Iterator<Item = Vec<Cell<'a>>> + 'a>(
+    mut grid_slice: GridSliceFilter,
+    filter: Option<LineFilter>,
+    separator: Option<Pattern>,
+    iter: I,
+) -> Box<dyn Iterator<Item = Line<'a>> + 'a> {
+    let range = grid_slice
+        .paragraph
+        .take()
+        .expect("Paragraph range must be specified");
+    let records = Records {
+        source: iter,
+        separator,
+        num_line: 0,
+    };
+    // Only one of them is used: the set replaces the range.
+    let set = grid_slice.paragraph_set.take();
+    let post_process = match set {
+        Some(ref s) => grid_slice::set_needs_post_process(s),
+        None => grid_slice::needs_post_process(&range),
+    };
+    let selected = |range: &GridSliceRange, ranges: &Option<MergedRanges>, n: usize| match ranges {
+        Some(r) => grid_slice::filter_by_ranges(r, n as i64),
+        None => grid_slice::filter_by_range(range, n as i64, range.exclude),
+    };
+    let records: Box<dyn Iterator<Item = Record<'a>> + 'a> = if post_process {
+        let mut records: Vec<Record<'a>> = records.collect();
+        let range = grid_slice::normalize_range(&range, records.len());
+        if range.step < 0 {
+            records.reverse();
+        }
+        let ranges = set.map(|s| grid_slice::merge_ranges(&s, records.len()));
+        Box::new(
+            records
+                .into_iter()
+                .enumerate()
+                .filter(move |(n, _)| selected(&range, &ranges, *n))
+                .map(|(_, r)| r),
+        )
+    } else {
+        let ranges = set.map(|s| grid_slice::merge_ranges(&s, grid_slice::UNKNOWN_LENGTH));
+        Box::new(
+            records
+                .enumerate()
+                .filter(move |(n, _)| selected(&range, &ranges, *n))
+                .map(|(_, r)| r),
+        )
+    };
+    Box::new(records.flat_map(move |record| {
+        let start = record.start;
+        let mut lines = grid_slice::grid_slice_iter(grid_slice.clone(), record.lines.into_iter());
+        if let Some(ref f) = filter {
+            lines = lines.with_filter(f.clone());
+        }
+        lines.map(move |mut l| {
+            l.number += start;
+            l
+        })
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::grid_slice_parser::parse_grid_slice;
+
+    fn run(slice: &str, separator: Option<&str>) -> Vec<String> {
+        let input = vec!["a 1", "b 2", "", "", "c 3", "--", "d 4", "", "e 5", "f 6"];
+        let lines = input
+            .into_iter()
+            .map(|l| l.split_whitespace().map(Cell::from).collect());
+        let separator = separator.map(|s| Pattern::new(s).unwrap());
+        paragraph_iter(parse_grid_slice(slice).unwrap(), None, separator, lines)
+            .map(|l| format!("{}:{}", l.number, l.cells.join(" ")))
+            .collect()
+    }
+
+    #[test]
+    fn paragraph_iter_test() {
+        assert_eq!(run("P1", None), vec!["4:c 3", "5:--", "6:d 4"]);
+        assert_eq!(run("P-1l1F0", None), vec!["9:f"]);
+        assert_eq!(run("p0:p1F1", None), vec!["0:1", "1:2", "4:3", "5:", "6:4"]);
+        assert_eq!(run("L0::p-1", None), vec!["8:e 5", "4:c 3", "0:a 1"]);
+        assert_eq!(run("!P1L0", None), vec!["0:a 1", "8:e 5"]);
+        assert_eq!(run("P1L0", Some("^--$")), vec!["6:d 4"]);
+        assert_eq!(run("P0,P-1L0", None), vec!["0:a 1", "8:e 5"]);
+        assert_eq!(run("P-1,!P1L0", None), vec!["8:e 5"]);
+    }
+}
@@ -0,0 +1,158 @@
+use std::io::{self, Write};
+use std::thread;
+
+use crate::fast_path::FastPath;
+use crate::parser::grid_slice_parser::GridSliceFilter;
+
+const CHUNK_SIZE: usize = 4 << 20;
+
+// Splits the input into chunks of about `size` bytes that end right after a separator
+// (except for the last one).
+fn split_chunks(data: &[u8], separator: u8, size: usize) -> Vec<&[u8]> {
+    let mut chunks = Vec::new();
+    let mut rest = data;
+    while rest.len() > size {
+        let end = match rest[size..].iter().position(|b| *b == separator) {
+            Some(n) => size + n + 1,
+            None => rest.len(),
+        };
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+fn count_records(chunk: &[u8], separator: u8) -> usize {
+    let separators = chunk.iter().filter(|b| **b == separator).count();
+    match chunk.last() {
+        Some(b) if *b != separator => separators + 1,
+        _ => separators,
+    }
+}
+
+// Processes the input the same way `FastPath` does, but chunks of the input are
+// processed in parallel. The separator must be a single byte, otherwise the chunks
+// could split a separator in the middle. Chunks are processed in batches of `threads` chunks: the
+// records of each chunk are counted first, so each chunk knows the index of its first
+// line, and then the outputs of the batch are written in the input order.
+pub fn run<W: Write>(
+    grid_slice: &GridSliceFilter,
+    data: &[u8],
+    separator: &[u8],
+    record_separator: &[u8],
+    line_numbers: bool,
+    threads: usize,
+    out: &mut W,
+) -> io::Result<()> {
+    run_chunks(
+        grid_slice,
+        &split_chunks(data, separator[0], CHUNK_SIZE),
+        separator,
+        record_separator,
+        line_numbers,
+        threads,
+        out,
+    )
+}
+
+fn run_chunks<W: Write>(
+    grid_slice: &GridSliceFilter,
+    chunks: &[&[u8]],
+    separator: &[u8],
+    record_separator: &[u8],
+    line_numbers: bool,
+    threads: usize,
+    out: &mut W,
+) -> io::Result<()> {
+    let separator_byte = separator[0];
+    let line = &grid_slice.line;
+    let mut first_line = 0;
+    for batch in chunks.chunks(threads.max(1)) {
+        // Nothing could be selected after the end of the range.
+        if !line.exclude && line.to != -1 && first_line as i64 > line.to {
+            break;
+        }
+        let counts: Vec<usize> = thread::scope(|s| {
+            let handles: Vec<_> = batch
+                .iter()
+                .map(|chunk| s.spawn(move || count_records(chunk, separator_byte)))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let outputs: Vec<io::Result<Vec<u8>>> = thread::scope(|s| {
+            let mut start = first_line;
+            let handles: Vec<_> = batch
+                .iter()
+                .zip(&counts)
+                .map(|(chunk, count)| {
+                    let first = start;
+                    start += count;
+                    s.spawn(move || {
+                        let mut output = Vec::new();
+                        FastPath::new(grid_slice, separator, record_separator, line_numbers)
+                            .run(*chunk, first, &mut output)
+                            .map(|_| output)
+                    })
+                })
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for output in outputs {
+            out.write_all(&output?)?;
+        }
+        first_line += counts.iter().sum::<usize>();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::grid_slice_parser::parse_grid_slice;
+
+    #[test]
+    fn split_chunks_test() {
+        let data = b"a b\ncc\n\nddd\ne";
+        assert_eq!(
+            split_chunks(data, b'\n', 2),
+            vec![&b"a b\n"[..], &b"cc\n"[..], &b"\nddd\n"[..], &b"e"[..]]
+        );
+        assert_eq!(split_chunks(data, b'\n', 100), vec![&data[..]]);
+        assert!(split_chunks(b"", b'\n', 2).is_empty());
+        assert_eq!(count_records(b"a\nb\n", b'\n'), 2);
+        assert_eq!(count_records(b"a\nb", b'\n'), 2);
+    }
+
+    #[test]
+    fn parallel_test() {
+        let input: String = (0..50)
+            .map(|n| format!("{} {} x{}\n", n, n * n, "y".repeat(n % 7)))
+            .collect();
+        for slice in &["l0", "F1", "l3F-1c1:l20c2:l4", "!l1:l40", "L7", "f[2,0]"] {
+            for line_numbers in &[false, true] {
+                let grid_slice = parse_grid_slice(slice).unwrap();
+                let mut expected = Vec::new();
+                FastPath::new(&grid_slice, b"\n", b"\n", *line_numbers)
+                    .run(input.as_bytes(), 0, &mut expected)
+                    .unwrap();
+                let chunks = split_chunks(input.as_bytes(), b'\n', 30);
+                let mut output = Vec::new();
+                run_chunks(
+                    &grid_slice,
+                    &chunks,
+                    b"\n",
+                    b"\n",
+                    *line_numbers,
+                    3,
+                    &mut output,
+                )
+                .unwrap();
+                assert_eq!(output, expected, "{}", slice);
+            }
+        }
+    }
+}
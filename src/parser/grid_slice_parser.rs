@@ -1,7 +1,12 @@
+use std::fmt;
+
 #[derive(Debug, PartialEq)]
 enum SliceEndpointInteger {
     LowercaseInteger(i64),
     UppercaseInteger(i64),
+    IntegerList(Vec<i64>),
+    // An offset from the `from` endpoint (`l+10`).
+    RelativeInteger(i64),
 }
 
 #[derive(Debug, PartialEq)]
@@ -12,10 +17,7 @@ struct SliceEndpoint {
 
 impl SliceEndpoint {
     fn new(int: SliceEndpointInteger, exclude: bool) -> Self {
-        SliceEndpoint {
-            int: int,
-            exclude: exclude,
-        }
+        SliceEndpoint { int, exclude }
     }
 
     fn from_lower(int: i64, exclude: bool) -> Self {
@@ -24,10 +26,17 @@ impl SliceEndpoint {
     fn from_upper(int: i64, exclude: bool) -> Self {
         SliceEndpoint::new(SliceEndpointInteger::UppercaseInteger(int), exclude)
     }
+    fn from_list(list: Vec<i64>, exclude: bool) -> Self {
+        SliceEndpoint::new(SliceEndpointInteger::IntegerList(list), exclude)
+    }
+    fn from_relative(int: i64, exclude: bool) -> Self {
+        SliceEndpoint::new(SliceEndpointInteger::RelativeInteger(int), exclude)
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 struct SliceIndex {
+    paragraph: Option<SliceEndpoint>,
     line: Option<SliceEndpoint>,
     field: Option<SliceEndpoint>,
     character: Option<SliceEndpoint>,
@@ -35,19 +44,25 @@ struct SliceIndex {
 
 /*
  * exclude = "!"
- * lowercase_line = "l" integer
+ * integer_list = "[" integer {"," integer} "]"
+ * relative = "+" digit {digit}
+ * lowercase_paragraph = "p" (integer | relative)
+ * uppercase_paragraph = "P" integer
+ * lowercase_line = "l" (integer | relative)
  * uppercase_line = "L" integer
- * lowercase_field = "f" integer
+ * lowercase_field = "f" (integer | integer_list | relative)
  * uppercase_field = "F" integer
- * lowercase_char = "c" integer
+ * lowercase_char = "c" (integer | integer_list | relative)
  * uppercase_char = "C" integer
+ * paragraph = [exclude] lowercase_paragraph | uppercase_paragraph
  * line = [exclude] lowercase_line | uppercase_line
  * field = [exclude] lowercase_field | uppercase_field
  * char = [exclude] lowercase_char | uppercase_char
- * grid_index = line [field] [char] | line [char] [field] | field [line] [char] | field [char] [line] | char [line] [field] | char [field] [line]
+ * grid_index = any non-empty combination of paragraph, line, field and char, each at most once and in any order
  * grid_slice = [grid_index] ':' [grid_index] [':' [grid_index]] | grid_index
+ * grid_slices = grid_slice {',' grid_slice}
  */
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
 struct Slice {
     from: SliceIndex,
     to: SliceIndex,
@@ -63,13 +78,13 @@ fn parse_exclude(it: &mut std::str::Chars) -> bool {
     false
 }
 
-fn parse_integer(it: &mut std::str::Chars) -> Option<i64> {
+pub(super) fn parse_integer(it: &mut std::str::Chars) -> Option<i64> {
     let peek = it.clone();
     // take_while() advances iterator past TWO elements after the last one. To work
     // around this issue we will have to advance the iterator manually.
     let integer = peek
         .enumerate()
-        .take_while(|(n, c)| c.is_digit(10) || (*n == 0 && *c == '-'))
+        .take_while(|(n, c)| c.is_ascii_digit() || (*n == 0 && *c == '-'))
         .map(|(_, c)| c)
         .collect::<String>();
     match integer.parse().ok() {
@@ -84,6 +99,37 @@ fn parse_integer(it: &mut std::str::Chars) -> Option<i64> {
     }
 }
 
+fn parse_integer_list(it: &mut std::str::Chars) -> Option<Vec<i64>> {
+    let mut peek = it.clone();
+    if peek.next()? != '[' {
+        return None;
+    }
+    let mut list = vec![parse_integer(&mut peek)?];
+    loop {
+        match peek.next()? {
+            ',' => list.push(parse_integer(&mut peek)?),
+            ']' => break,
+            _ => return None,
+        }
+    }
+    *it = peek;
+    Some(list)
+}
+
+fn parse_relative(it: &mut std::str::Chars) -> Option<i64> {
+    let mut peek = it.clone();
+    if peek.next()? != '+' {
+        return None;
+    }
+    match parse_integer(&mut peek) {
+        Some(v) if v >= 0 => {
+            *it = peek;
+            Some(v)
+        }
+        _ => None,
+    }
+}
+
 fn parse_separator(it: &mut std::str::Chars) -> bool {
     let mut peek = it.clone();
     if peek.next() == Some(':') {
@@ -93,6 +139,15 @@ fn parse_separator(it: &mut std::str::Chars) -> bool {
     false
 }
 
+fn parse_range_separator(it: &mut std::str::Chars) -> bool {
+    let mut peek = it.clone();
+    if peek.next() == Some(',') {
+        *it = peek;
+        return true;
+    }
+    false
+}
+
 fn parse_endpoint(it: &mut std::str::Chars, prefix: char) -> Option<SliceEndpoint> {
     let mut peek = it.clone();
     let exclude = parse_exclude(&mut peek);
@@ -105,10 +160,27 @@ fn parse_endpoint(it: &mut std::str::Chars, prefix: char) -> Option<SliceEndpoin
                 return Some(SliceEndpoint::from_upper(v, exclude));
             }
         }
+        if prefix.is_lowercase() {
+            if let Some(list) = parse_integer_list(&mut peek) {
+                *it = peek;
+                return Some(SliceEndpoint::from_list(list, exclude));
+            }
+            if let Some(offset) = parse_relative(&mut peek) {
+                *it = peek;
+                return Some(SliceEndpoint::from_relative(offset, exclude));
+            }
+        }
     }
     None
 }
 
+fn parse_paragraph(it: &mut std::str::Chars) -> Option<SliceEndpoint> {
+    match parse_endpoint(it, 'p') {
+        Some(v) => Some(v),
+        None => parse_endpoint(it, 'P'),
+    }
+}
+
 fn parse_line(it: &mut std::str::Chars) -> Option<SliceEndpoint> {
     match parse_endpoint(it, 'l') {
         Some(v) => Some(v),
@@ -132,100 +204,51 @@ fn parse_char(it: &mut std::str::Chars) -> Option<SliceEndpoint> {
 
 fn parse_grid_index(it: &mut std::str::Chars) -> Option<SliceIndex> {
     let mut peek = it.clone();
-    let gi: Option<SliceIndex> = if let Some(line) = parse_line(&mut peek) {
-        if let Some(field) = parse_field(&mut peek) {
-            Some(SliceIndex {
-                line: Some(line),
-                field: Some(field),
-                character: parse_char(&mut peek),
-            })
-        } else if let Some(character) = parse_char(&mut peek) {
-            Some(SliceIndex {
-                line: Some(line),
-                field: parse_field(&mut peek),
-                character: Some(character),
-            })
-        } else {
-            Some(SliceIndex {
-                line: Some(line),
-                field: None,
-                character: None,
-            })
+    let mut gi = SliceIndex::default();
+    // Each dimension could be specified at most once but in any order.
+    loop {
+        if gi.paragraph.is_none() {
+            gi.paragraph = parse_paragraph(&mut peek);
+            if gi.paragraph.is_some() {
+                continue;
+            }
         }
-    } else if let Some(field) = parse_field(&mut peek) {
-        if let Some(line) = parse_line(&mut peek) {
-            Some(SliceIndex {
-                line: Some(line),
-                field: Some(field),
-                character: parse_char(&mut peek),
-            })
-        } else if let Some(character) = parse_char(&mut peek) {
-            Some(SliceIndex {
-                line: parse_line(&mut peek),
-                field: Some(field),
-                character: Some(character),
-            })
-        } else {
-            Some(SliceIndex {
-                line: None,
-                field: Some(field),
-                character: None,
-            })
+        if gi.line.is_none() {
+            gi.line = parse_line(&mut peek);
+            if gi.line.is_some() {
+                continue;
+            }
         }
-    } else if let Some(character) = parse_char(&mut peek) {
-        if let Some(line) = parse_line(&mut peek) {
-            Some(SliceIndex {
-                line: Some(line),
-                field: parse_field(&mut peek),
-                character: Some(character),
-            })
-        } else if let Some(field) = parse_field(&mut peek) {
-            Some(SliceIndex {
-                line: parse_line(&mut peek),
-                field: Some(field),
-                character: Some(character),
-            })
-        } else {
-            Some(SliceIndex {
-                line: None,
-                field: None,
-                character: Some(character),
-            })
+        if gi.field.is_none() {
+            gi.field = parse_field(&mut peek);
+            if gi.field.is_some() {
+                continue;
+            }
         }
-    } else {
-        None
-    };
-    if gi.is_some() {
-        *it = peek;
+        if gi.character.is_none() {
+            gi.character = parse_char(&mut peek);
+            if gi.character.is_some() {
+                continue;
+            }
+        }
+        break;
     }
-    gi
+    if gi == SliceIndex::default() {
+        return None;
+    }
+    *it = peek;
+    Some(gi)
 }
 
 // grid_slice = [grid_index] ':' [grid_index] [':' [grid_index]] | grid_index
 fn parse_grid_slice_impl(it: &mut std::str::Chars) -> Option<Slice> {
     let mut peek = it.clone();
-    let mut gs = Slice {
-        from: SliceIndex {
-            line: None,
-            field: None,
-            character: None,
-        },
-        to: SliceIndex {
-            line: None,
-            field: None,
-            character: None,
-        },
-        step: SliceIndex {
-            line: None,
-            field: None,
-            character: None,
-        },
-    };
+    let mut gs = Slice::default();
     if let Some(from_grid_index) = parse_grid_index(&mut peek) {
         gs.from = from_grid_index;
     }
     if !parse_separator(&mut peek) {
-        if gs.from.line.is_none() && gs.from.field.is_none() {
+        if gs.from == SliceIndex::default() {
             return None;
         } else {
             *it = peek;
@@ -246,30 +269,125 @@ fn parse_grid_slice_impl(it: &mut std::str::Chars) -> Option<Slice> {
     Some(gs)
 }
 
-#[derive(Debug)]
+// grid_slices = grid_slice {',' grid_slice}
+fn parse_grid_slices(it: &mut std::str::Chars) -> Option<Vec<Slice>> {
+    let mut peek = it.clone();
+    let mut slices = vec![parse_grid_slice_impl(&mut peek)?];
+    while parse_range_separator(&mut peek) {
+        slices.push(parse_grid_slice_impl(&mut peek)?);
+    }
+    *it = peek;
+    Some(slices)
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct GridSliceRange {
     pub from: i64,
     pub to: i64,
     pub step: i64,
     pub exclude: bool,
+    // `to` is excluded and the indexes are clamped the same way Python does it. Until
+    // the range is normalized, omitted endpoints are i64::MAX/MIN for the end/start.
+    pub exclusive: bool,
+    // `to` is an offset from `from` (`l-5:l+2`). Offsets are resolved while parsing
+    // unless `from` counts from the end.
+    pub relative: bool,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct GridSliceFilter {
+    // None unless paragraphs were specified in the slice.
+    pub paragraph: Option<GridSliceRange>,
     pub line: GridSliceRange,
     pub field: GridSliceRange,
     pub character: GridSliceRange,
+    // Explicit lists of indexes (`f[3,0,1]`) that replace the ranges above.
+    pub field_list: Option<Vec<i64>>,
+    pub character_list: Option<Vec<i64>>,
+    // Several ranges separated by commas (`:c2,c-3`) that replace the ranges above.
+    // Paragraph sets come with a default paragraph range.
+    pub paragraph_set: Option<RangeSet>,
+    pub line_set: Option<RangeSet>,
+    pub field_set: Option<RangeSet>,
+    pub character_set: Option<RangeSet>,
+}
+
+// An item is selected if it's inside any of the ranges without "!" (or all the ranges
+// have it) and outside of all the ranges with "!". Selected items keep their order, so
+// negative steps are not allowed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RangeSet {
+    pub ranges: Vec<GridSliceRange>,
+}
+
+#[derive(Default)]
+struct ValidRange {
+    from: Option<i64>,
+    to: Option<i64>,
+    step: Option<i64>,
+    exclude: bool,
+    list: Option<Vec<i64>>,
+    // Set by uppercase endpoints.
+    single: bool,
+    // `to` is an offset from `from`.
+    relative: bool,
+}
+
+impl ValidRange {
+    // Ranges of a single item are the same in both modes.
+    fn to_range(&self, exclusive: bool) -> GridSliceRange {
+        let step = self.step.unwrap_or(1);
+        let exclusive = exclusive && !self.single && self.list.is_none();
+        let (default_from, default_to) = default_endpoints(exclusive, step);
+        let from = self.from.unwrap_or(default_from);
+        // Offsets from indexes that count from the end can be resolved only when the
+        // length of the input is known.
+        let (to, relative) = match self.to {
+            Some(offset) if self.relative && from >= 0 => (from.saturating_add(offset), false),
+            Some(offset) if self.relative => (offset, true),
+            to => (to.unwrap_or(default_to), false),
+        };
+        GridSliceRange {
+            from,
+            to,
+            step,
+            exclude: self.exclude,
+            exclusive,
+            relative,
+        }
+    }
+
+    // Lists are split into ranges of a single item when they are a part of a range set.
+    fn to_ranges(&self, exclusive: bool) -> Vec<GridSliceRange> {
+        match self.list {
+            Some(ref list) => list
+                .iter()
+                .map(|i| GridSliceRange {
+                    from: *i,
+                    to: *i,
+                    step: 1,
+                    exclude: self.exclude,
+                    exclusive: false,
+                    relative: false,
+                })
+                .collect(),
+            None => vec![self.to_range(exclusive)],
+        }
+    }
 }
 
 fn extract_valid_range(
     from: &Option<SliceEndpoint>,
     to: &Option<SliceEndpoint>,
     step: &Option<SliceEndpoint>,
-) -> Result<(Option<i64>, Option<i64>, Option<i64>, bool), &'static str> {
+) -> Result<ValidRange, &'static str> {
     let mut from_int: Option<i64> = None;
     let mut to_int: Option<i64> = None;
     let mut step_int: Option<i64> = None;
+    let mut list: Option<Vec<i64>> = None;
     let mut exclude = false;
+    let mut single = false;
+    let mut relative = false;
     if let Some(from) = from {
         match from.int {
             SliceEndpointInteger::LowercaseInteger(i) => {
@@ -278,6 +396,16 @@ fn extract_valid_range(
             SliceEndpointInteger::UppercaseInteger(i) => {
                 from_int = Some(i);
                 to_int = Some(i);
+                single = true;
+            }
+            SliceEndpointInteger::IntegerList(ref l) => {
+                if to.is_some() || step.is_some() {
+                    return Err("Index list cannot be used in conjunction with other endpoints of the same range");
+                }
+                list = Some(l.clone());
+            }
+            SliceEndpointInteger::RelativeInteger(_) => {
+                return Err("Relative endpoint can be specified only in place of 'to'");
             }
         }
         exclude = exclude || from.exclude;
@@ -297,6 +425,17 @@ fn extract_valid_range(
                 }
                 from_int = Some(i);
                 to_int = Some(i);
+                single = true;
+            }
+            SliceEndpointInteger::IntegerList(_) => {
+                return Err("Index list can be specified only in place of 'from'");
+            }
+            SliceEndpointInteger::RelativeInteger(i) => {
+                if to_int.is_some() {
+                    return Err("Ambiguous range specified. 'L'/'F' was used in conjunction with 'l'/'f' for the same range");
+                }
+                to_int = Some(i);
+                relative = true;
             }
         }
         exclude = exclude || to.exclude;
@@ -304,22 +443,49 @@ fn extract_valid_range(
 
     if let Some(step) = step {
         match step.int {
+            SliceEndpointInteger::LowercaseInteger(0) => {
+                return Err("Step cannot be zero");
+            }
             SliceEndpointInteger::LowercaseInteger(i) => {
                 step_int = Some(i);
             }
             SliceEndpointInteger::UppercaseInteger(_) => {
                 return Err("Step specifier cannot be uppercase");
             }
+            SliceEndpointInteger::IntegerList(_) => {
+                return Err("Index list can be specified only in place of 'from'");
+            }
+            SliceEndpointInteger::RelativeInteger(_) => {
+                return Err("Relative endpoint can be specified only in place of 'to'");
+            }
         }
         exclude = exclude || step.exclude;
     }
 
-    Ok((from_int, to_int, step_int, exclude))
+    Ok(ValidRange {
+        from: from_int,
+        to: to_int,
+        step: step_int,
+        exclude,
+        list,
+        single,
+        relative,
+    })
+}
+
+pub fn parse_grid_slice(input: &str) -> Result<GridSliceFilter, &'static str> {
+    parse_grid_slice_mode(input, false)
+}
+
+// The same as `parse_grid_slice()`, but `to` is exclusive and the ranges follow
+// Python's rules (e.g. `l-3:l-1` are the two lines before the last one).
+pub fn parse_exclusive_grid_slice(input: &str) -> Result<GridSliceFilter, &'static str> {
+    parse_grid_slice_mode(input, true)
 }
 
-pub fn parse_grid_slice(input: &String) -> Result<GridSliceFilter, &'static str> {
-    let mut chars = input.chars().into_iter();
-    let pgs = match parse_grid_slice_impl(&mut chars) {
+fn parse_grid_slice_mode(input: &str, exclusive: bool) -> Result<GridSliceFilter, &'static str> {
+    let mut chars = input.chars();
+    let slices = match parse_grid_slices(&mut chars) {
         Some(v) => v,
         None => return Err("Unable to parse the input"),
     };
@@ -327,32 +493,321 @@ pub fn parse_grid_slice(input: &String) -> Result<GridSliceFilter, &'static str>
         return Err("Unable to fully parse the input");
     }
 
-    let line_range = extract_valid_range(&pgs.from.line, &pgs.to.line, &pgs.step.line)?;
-    let field_range = extract_valid_range(&pgs.from.field, &pgs.to.field, &pgs.step.field)?;
-    let character_range =
-        extract_valid_range(&pgs.from.character, &pgs.to.character, &pgs.step.character)?;
+    let paragraph_ranges = dimension_ranges(&slices, |i| &i.paragraph)?;
+    let line_ranges = dimension_ranges(&slices, |i| &i.line)?;
+    let field_ranges = dimension_ranges(&slices, |i| &i.field)?;
+    let character_ranges = dimension_ranges(&slices, |i| &i.character)?;
+    if line_ranges
+        .iter()
+        .chain(&paragraph_ranges)
+        .any(|r| r.list.is_some())
+    {
+        return Err("Index list cannot be used for lines and paragraphs");
+    }
+    let paragraph_set = range_set(&paragraph_ranges, exclusive)?;
+    let line_set = range_set(&line_ranges, exclusive)?;
+    let field_set = range_set(&field_ranges, exclusive)?;
+    let character_set = range_set(&character_ranges, exclusive)?;
+    // Dimensions with a range set or without ranges get the default range.
+    let single = |mut ranges: Vec<ValidRange>| match ranges.len() {
+        1 => ranges.pop(),
+        _ => None,
+    };
+    let paragraph = if paragraph_ranges.is_empty() {
+        None
+    } else {
+        Some(
+            single(paragraph_ranges)
+                .unwrap_or_default()
+                .to_range(exclusive),
+        )
+    };
+    let line_range = single(line_ranges).unwrap_or_default();
+    let field_range = single(field_ranges).unwrap_or_default();
+    let character_range = single(character_ranges).unwrap_or_default();
     Ok(GridSliceFilter {
-        line: GridSliceRange {
-            from: line_range.0.unwrap_or(0),
-            to: line_range.1.unwrap_or(-1),
-            step: line_range.2.unwrap_or(1),
-            exclude: line_range.3,
-        },
-        field: GridSliceRange {
-            from: field_range.0.unwrap_or(0),
-            to: field_range.1.unwrap_or(-1),
-            step: field_range.2.unwrap_or(1),
-            exclude: field_range.3,
-        },
-        character: GridSliceRange {
-            from: character_range.0.unwrap_or(0),
-            to: character_range.1.unwrap_or(-1),
-            step: character_range.2.unwrap_or(1),
-            exclude: character_range.3,
-        },
+        paragraph,
+        line: line_range.to_range(exclusive),
+        field: field_range.to_range(exclusive),
+        character: character_range.to_range(exclusive),
+        field_list: field_range.list,
+        character_list: character_range.list,
+        paragraph_set,
+        line_set,
+        field_set,
+        character_set,
     })
 }
 
+// Returns the ranges of a dimension from the slices that mention it.
+fn dimension_ranges(
+    slices: &[Slice],
+    dimension: fn(&SliceIndex) -> &Option<SliceEndpoint>,
+) -> Result<Vec<ValidRange>, &'static str> {
+    slices
+        .iter()
+        .filter(|s| {
+            [&s.from, &s.to, &s.step]
+                .iter()
+                .any(|i| dimension(i).is_some())
+        })
+        .map(|s| extract_valid_range(dimension(&s.from), dimension(&s.to), dimension(&s.step)))
+        .collect()
+}
+
+fn range_set(ranges: &[ValidRange], exclusive: bool) -> Result<Option<RangeSet>, &'static str> {
+    if ranges.len() < 2 {
+        return Ok(None);
+    }
+    let ranges: Vec<GridSliceRange> = ranges.iter().flat_map(|r| r.to_ranges(exclusive)).collect();
+    if ranges.iter().any(|r| r.step < 0) {
+        return Err("Negative step cannot be used with several ranges");
+    }
+    Ok(Some(RangeSet { ranges }))
+}
+
+// Returns the values of omitted from and to endpoints.
+fn default_endpoints(exclusive: bool, step: i64) -> (i64, i64) {
+    match (exclusive, step > 0) {
+        (false, _) => (0, -1),
+        (true, true) => (0, i64::MAX),
+        (true, false) => (i64::MAX, i64::MIN),
+    }
+}
+
+// Returns the from, to and step endpoints of a range that differ from the defaults.
+fn range_endpoints(range: &GridSliceRange, list: &Option<Vec<i64>>, prefix: char) -> [String; 3] {
+    let mut endpoints = [String::new(), String::new(), String::new()];
+    if let Some(l) = list {
+        let list: Vec<String> = l.iter().map(i64::to_string).collect();
+        endpoints[0] = format!("{}[{}]", prefix, list.join(","));
+    } else if range.from == range.to && !range.exclusive && !range.relative {
+        endpoints[0] = format!("{}{}", prefix.to_ascii_uppercase(), range.from);
+    } else {
+        let (from, to) = default_endpoints(range.exclusive, range.step);
+        if range.from != from {
+            endpoints[0] = format!("{}{}", prefix, range.from);
+        }
+        if range.relative {
+            endpoints[1] = format!("{}+{}", prefix, range.to);
+        } else if range.to != to {
+            endpoints[1] = format!("{}{}", prefix, range.to);
+        }
+    }
+    if range.step != 1 {
+        endpoints[2] = format!("{}{}", prefix, range.step);
+    }
+    // The exclusion applies to the whole range, so it's enough to mark one endpoint.
+    if range.exclude {
+        match endpoints.iter_mut().find(|e| !e.is_empty()) {
+            Some(e) => e.insert(0, '!'),
+            None => endpoints[0] = format!("!{}0", prefix),
+        }
+    }
+    endpoints
+}
+
+// Joins the endpoints of all the dimensions into `from:to:step`, leaving out the empty
+// parts at the end.
+fn join_endpoints(parts: [String; 3]) -> String {
+    let [from, to, step] = parts;
+    if !step.is_empty() {
+        format!("{}:{}:{}", from, to, step)
+    } else if !to.is_empty() || from.is_empty() {
+        format!("{}:{}", from, to)
+    } else {
+        from
+    }
+}
+
+// Prints the shortest slice that is parsed back into the same filter: endpoints with
+// default values are omitted and ranges of a single item use uppercase letters. Each
+// range of a range set is printed as a separate slice.
+impl fmt::Display for GridSliceFilter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut parts = [String::new(), String::new(), String::new()];
+        let mut add = |endpoints: [String; 3]| {
+            for (part, endpoint) in parts.iter_mut().zip(endpoints.iter()) {
+                part.push_str(endpoint);
+            }
+        };
+        match self.paragraph {
+            Some(ref paragraph) if self.paragraph_set.is_none() => {
+                let mut endpoints = range_endpoints(paragraph, &None, 'p');
+                // Paragraphs have to be mentioned even if they are all selected.
+                if endpoints.iter().all(String::is_empty) {
+                    endpoints[0] = "p0".to_string();
+                }
+                add(endpoints);
+            }
+            _ => (),
+        }
+        if self.line_set.is_none() {
+            add(range_endpoints(&self.line, &None, 'l'));
+        }
+        if self.field_set.is_none() {
+            add(range_endpoints(&self.field, &self.field_list, 'f'));
+        }
+        if self.character_set.is_none() {
+            add(range_endpoints(&self.character, &self.character_list, 'c'));
+        }
+        let mut slices = Vec::new();
+        if parts.iter().any(|p| !p.is_empty()) {
+            slices.push(join_endpoints(parts));
+        }
+        let sets = [
+            (&self.paragraph_set, 'p'),
+            (&self.line_set, 'l'),
+            (&self.field_set, 'f'),
+            (&self.character_set, 'c'),
+        ];
+        for (set, prefix) in &sets {
+            for range in set.iter().flat_map(|s| &s.ranges) {
+                let mut endpoints = range_endpoints(range, &None, *prefix);
+                // Ranges of the set have to be mentioned even if they select everything.
+                if endpoints.iter().all(String::is_empty) {
+                    endpoints[0] = format!("{}0", prefix);
+                }
+                slices.push(join_endpoints(endpoints));
+            }
+        }
+        if slices.is_empty() {
+            write!(f, ":")
+        } else {
+            write!(f, "{}", slices.join(","))
+        }
+    }
+}
+
+fn ordinal(n: u64) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (1, 11) | (2, 12) | (3, 13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+fn describe_index(index: i64) -> String {
+    match index {
+        -1 => "the last".to_string(),
+        i if i < 0 => format!("the {} from the end", ordinal(i.unsigned_abs())),
+        i => i.to_string(),
+    }
+}
+
+fn describe_range(range: &GridSliceRange, list: &Option<Vec<i64>>) -> String {
+    let mut text = if let Some(l) = list {
+        let mut items: Vec<String> = l.iter().map(|i| describe_index(*i)).collect();
+        let last = items.pop().unwrap_or_default();
+        if items.is_empty() {
+            last
+        } else {
+            format!("{} and {}", items.join(", "), last)
+        }
+    } else if range.relative {
+        format!(
+            "{} to {} after it{}",
+            describe_index(range.from),
+            range.to,
+            if range.exclusive { " (exclusive)" } else { "" }
+        )
+    } else if range.from == range.to && !range.exclusive {
+        describe_index(range.from)
+    } else if (range.from, range.to) == default_endpoints(range.exclusive, range.step) {
+        "all".to_string()
+    } else if range.exclusive {
+        let from = match range.from {
+            i64::MAX => "the last".to_string(),
+            i => describe_index(i),
+        };
+        let to = match range.to {
+            i64::MAX => "the last".to_string(),
+            i64::MIN => "0".to_string(),
+            i => format!("{} (exclusive)", describe_index(i)),
+        };
+        format!("{} to {}", from, to)
+    } else {
+        format!(
+            "{} to {}",
+            describe_index(range.from),
+            describe_index(range.to)
+        )
+    };
+    if range.exclude {
+        text = format!("all except {}", text);
+    }
+    if range.step.unsigned_abs() > 1 {
+        text.push_str(&format!(", every {}", ordinal(range.step.unsigned_abs())));
+    }
+    if range.step < 0 {
+        text.push_str(", in reverse order");
+    }
+    text
+}
+
+fn describe_range_set(set: &RangeSet) -> String {
+    let (excluded, included): (Vec<&GridSliceRange>, Vec<&GridSliceRange>) =
+        set.ranges.iter().partition(|r| r.exclude);
+    let join = |ranges: Vec<&GridSliceRange>| {
+        let texts: Vec<String> = ranges
+            .iter()
+            .map(|r| {
+                describe_range(
+                    &GridSliceRange {
+                        exclude: false,
+                        ..(*r).clone()
+                    },
+                    &None,
+                )
+            })
+            .collect();
+        texts.join(" and ")
+    };
+    let text = if included.is_empty() {
+        "all".to_string()
+    } else {
+        join(included)
+    };
+    if excluded.is_empty() {
+        text
+    } else {
+        format!("{} except {}", text, join(excluded))
+    }
+}
+
+impl GridSliceFilter {
+    // Describes what the slice selects in each dimension, one dimension per line.
+    pub fn explain(&self) -> String {
+        let mut text = format!("slice: {}\n", self);
+        if let Some(ref paragraph) = self.paragraph {
+            let paragraphs = match self.paragraph_set {
+                Some(ref set) => describe_range_set(set),
+                None => describe_range(paragraph, &None),
+            };
+            text.push_str(&format!("paragraphs: {}\n", paragraphs));
+        }
+        let lines = match self.line_set {
+            Some(ref set) => describe_range_set(set),
+            None => describe_range(&self.line, &None),
+        };
+        text.push_str(&format!("lines: {}\n", lines));
+        let words = match self.field_set {
+            Some(ref set) => describe_range_set(set),
+            None => describe_range(&self.field, &self.field_list),
+        };
+        text.push_str(&format!("words: {}\n", words));
+        let characters = match self.character_set {
+            Some(ref set) => describe_range_set(set),
+            None => describe_range(&self.character, &self.character_list),
+        };
+        text.push_str(&format!("characters: {}\n", characters));
+        text
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -377,6 +832,21 @@ mod tests {
         parse_test_helper(parse_integer, "42-abc", Some(42), Some('-'));
     }
 
+    #[test]
+    fn parse_integer_list_test() {
+        parse_test_helper(parse_integer_list, "[42]", Some(vec![42]), None);
+        parse_test_helper(
+            parse_integer_list,
+            "[3,0,-1,0]abc",
+            Some(vec![3, 0, -1, 0]),
+            Some('a'),
+        );
+        parse_test_helper(parse_integer_list, "[]", None, Some('['));
+        parse_test_helper(parse_integer_list, "[1,]", None, Some('['));
+        parse_test_helper(parse_integer_list, "[1", None, Some('['));
+        parse_test_helper(parse_integer_list, "42", None, Some('4'));
+    }
+
     #[test]
     fn parse_separator_test() {
         parse_test_helper(parse_separator, ":abc", true, Some('a'));
@@ -461,6 +931,13 @@ mod tests {
             Some(SliceEndpoint::from_upper(-123, false)),
             Some('a'),
         );
+        parse_test_helper(
+            parse_field,
+            "!f[3,0,1]abc",
+            Some(SliceEndpoint::from_list(vec![3, 0, 1], true)),
+            Some('a'),
+        );
+        parse_test_helper(parse_field, "F[3,0,1]", None, Some('F'));
     }
 
     #[test]
@@ -506,9 +983,10 @@ mod tests {
             character: Option<SliceEndpoint>,
         ) -> Option<SliceIndex> {
             Some(SliceIndex {
-                line: line,
-                field: field,
-                character: character,
+                paragraph: None,
+                line,
+                field,
+                character,
             })
         }
         parse_test_helper(
@@ -541,6 +1019,17 @@ mod tests {
         parse_test_helper(parse_grid_index, "f2l1c3", pi(pl(1), pl(2), pl(3)), None);
         parse_test_helper(parse_grid_index, "c3f2l1", pi(pl(1), pl(2), pl(3)), None);
         parse_test_helper(parse_grid_index, "c3l1f2", pi(pl(1), pl(2), pl(3)), None);
+        parse_test_helper(
+            parse_grid_index,
+            "f2P-1l1",
+            Some(SliceIndex {
+                paragraph: pu(-1),
+                line: pl(1),
+                field: pl(2),
+                character: None,
+            }),
+            None,
+        );
     }
 
     #[test]
@@ -557,18 +1046,15 @@ mod tests {
             character: Option<SliceEndpoint>,
         ) -> SliceIndex {
             SliceIndex {
-                line: line,
-                field: field,
-                character: character,
+                paragraph: None,
+                line,
+                field,
+                character,
             }
         }
 
         fn ps(from: SliceIndex, to: SliceIndex, step: SliceIndex) -> Slice {
-            Slice {
-                from: from,
-                to: to,
-                step: step,
-            }
+            Slice { from, to, step }
         }
         parse_test_helper(parse_grid_slice_impl, "", None, None);
         parse_test_helper(
@@ -621,6 +1107,16 @@ mod tests {
             )),
             Some(':'),
         );
+        parse_test_helper(
+            parse_grid_slice_impl,
+            "C3",
+            Some(ps(
+                pi(None, None, pu(3)),
+                pi(None, None, None),
+                pi(None, None, None),
+            )),
+            None,
+        );
         parse_test_helper(
             parse_grid_slice_impl,
             ":l42F0",
@@ -652,4 +1148,175 @@ mod tests {
             Some('a'),
         );
     }
+
+    #[test]
+    fn parse_grid_slice_test() {
+        assert_eq!(parse_grid_slice("::l0"), Err("Step cannot be zero"));
+        assert_eq!(parse_grid_slice("-"), Err("Unable to parse the input"));
+        assert_eq!(
+            parse_grid_slice("l99999999999999999999"),
+            Err("Unable to parse the input")
+        );
+    }
+
+    #[test]
+    fn display_test() {
+        let normalize = |slice: &str| parse_grid_slice(slice).unwrap().to_string();
+        assert_eq!(normalize("l0f1c0:l-1f1c-1:l1f1c1"), "F1");
+        assert_eq!(normalize("l0f0c0:l-1f-1c-1:l1f1c1"), ":");
+        assert_eq!(normalize("l10:l15:l-2"), "l10:l15:l-2");
+        assert_eq!(normalize("l1:!l-2"), "!l1:l-2");
+        assert_eq!(normalize("::!f1"), "!f0");
+        assert_eq!(normalize("f[0,-1]p2:p2"), "P2f[0,-1]");
+        assert_eq!(normalize("p0:p-1"), "p0");
+        assert_eq!(normalize("l2:l2:l3"), "L2::l3");
+        for slice in &[
+            "l10:l15:l-2",
+            "!l1:l-2",
+            "L-1C0",
+            "!c[1,3]::f-1",
+            "f2P-1l1",
+            ":",
+        ] {
+            let grid_slice = parse_grid_slice(slice).unwrap();
+            assert_eq!(
+                parse_grid_slice(&grid_slice.to_string()),
+                Ok(grid_slice),
+                "{}",
+                slice
+            );
+        }
+    }
+
+    #[test]
+    fn relative_test() {
+        let grid_slice = parse_grid_slice("f3l-5:f+2l+1").unwrap();
+        assert_eq!((grid_slice.field.to, grid_slice.field.relative), (5, false));
+        assert_eq!((grid_slice.line.to, grid_slice.line.relative), (1, true));
+        assert_eq!(grid_slice.to_string(), "l-5f3:l+1f5");
+        assert_eq!(parse_grid_slice(":l+2").unwrap().line.to, 2);
+        assert_eq!(
+            parse_grid_slice("l+2"),
+            Err("Relative endpoint can be specified only in place of 'to'")
+        );
+        assert_eq!(
+            parse_grid_slice("L1:l+2"),
+            Err("Ambiguous range specified. 'L'/'F' was used in conjunction with 'l'/'f' for the same range")
+        );
+        assert_eq!(
+            parse_grid_slice("l1:l+-2"),
+            Err("Unable to fully parse the input")
+        );
+        assert_eq!(
+            parse_grid_slice("l-5:l+1").unwrap().explain(),
+            "slice: l-5:l+1\n\
+             lines: the 5th from the end to 1 after it\n\
+             words: all\n\
+             characters: all\n"
+        );
+    }
+
+    #[test]
+    fn exclusive_test() {
+        let range = |from, to, step| GridSliceRange {
+            from,
+            to,
+            step,
+            exclude: false,
+            exclusive: true,
+            relative: false,
+        };
+        let grid_slice = parse_exclusive_grid_slice("l1:l3:f-1").unwrap();
+        assert_eq!(grid_slice.line, range(1, 3, 1));
+        assert_eq!(grid_slice.field, range(i64::MAX, i64::MIN, -1));
+        assert_eq!(grid_slice.to_string(), "l1:l3:f-1");
+        // Single items are not affected.
+        let grid_slice = parse_exclusive_grid_slice("L-1f[0]").unwrap();
+        assert!(!grid_slice.line.exclusive && !grid_slice.field.exclusive);
+        assert_eq!(
+            parse_exclusive_grid_slice("l2C0:l-1:l-2")
+                .unwrap()
+                .explain(),
+            "slice: l2C0:l-1:l-2\n\
+             lines: 2 to the last (exclusive), every 2nd, in reverse order\n\
+             words: all\n\
+             characters: 0\n"
+        );
+    }
+
+    #[test]
+    fn range_set_test() {
+        let range = |from, to, exclude| GridSliceRange {
+            from,
+            to,
+            step: 1,
+            exclude,
+            exclusive: false,
+            relative: false,
+        };
+        let grid_slice = parse_grid_slice("l1:c2,c-3,!f[0,2]").unwrap();
+        assert_eq!(grid_slice.line, range(1, -1, false));
+        assert_eq!(
+            grid_slice.character_set,
+            Some(RangeSet {
+                ranges: vec![range(0, 2, false), range(-3, -1, false)]
+            })
+        );
+        assert_eq!(grid_slice.field_list, Some(vec![0, 2]));
+        assert_eq!(grid_slice.field_set, None);
+        assert_eq!(grid_slice.to_string(), "l1!f[0,2],:c2,c-3");
+        assert_eq!(
+            parse_grid_slice("F0,!F2").unwrap().field_set,
+            Some(RangeSet {
+                ranges: vec![range(0, 0, false), range(2, 2, true)]
+            })
+        );
+        assert_eq!(parse_grid_slice("c0,!C1").unwrap().to_string(), "c0,!C1");
+        let grid_slice = parse_grid_slice("l1:l3,l10:l12,p0,!P2").unwrap();
+        assert_eq!(
+            grid_slice.line_set,
+            Some(RangeSet {
+                ranges: vec![range(1, 3, false), range(10, 12, false)]
+            })
+        );
+        assert_eq!(grid_slice.paragraph, Some(range(0, -1, false)));
+        assert_eq!(grid_slice.to_string(), "p0,!P2,l1:l3,l10:l12");
+        assert_eq!(
+            parse_grid_slice("l1,l[2]"),
+            Err("Index list cannot be used for lines and paragraphs")
+        );
+        assert_eq!(
+            parse_grid_slice("F0,f-1::f-1"),
+            Err("Negative step cannot be used with several ranges")
+        );
+        assert_eq!(parse_grid_slice("F0,"), Err("Unable to parse the input"));
+        assert_eq!(
+            parse_grid_slice(":c2,c-3,!C1").unwrap().explain(),
+            "slice: :c2,c-3,!C1\n\
+             lines: all\n\
+             words: all\n\
+             characters: 0 to 2 and the 3rd from the end to the last except 1\n"
+        );
+    }
+
+    #[test]
+    fn explain_test() {
+        assert_eq!(
+            parse_grid_slice("l10F-1:l15:l-2").unwrap().explain(),
+            "slice: l10F-1:l15:l-2\n\
+             lines: 10 to 15, every 2nd, in reverse order\n\
+             words: the last\n\
+             characters: all\n"
+        );
+        assert_eq!(
+            parse_grid_slice("P0!l1f[3,0,-2]!c[0]:l-2")
+                .unwrap()
+                .explain(),
+            "slice: P0!l1f[3,0,-2]!c[0]:l-2\n\
+             paragraphs: 0\n\
+             lines: all except 1 to the 2nd from the end\n\
+             words: 3, 0 and the 2nd from the end\n\
+             characters: all except 0\n"
+        );
+    }
 }
@@ -0,0 +1,234 @@
+use super::grid_slice_parser::parse_integer;
+use crate::pattern::Pattern;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldRef {
+    Index(i64),
+    // A field named in the first line of the input.
+    Name(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Predicate {
+    Compare(FieldRef, Operator, String),
+    Match(FieldRef, Pattern),
+    NotMatch(FieldRef, Pattern),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+/*
+ * field = "F" integer | "F{" name "}"
+ * operator = "==" | "!=" | "<=" | ">=" | "<" | ">"
+ * value = '"' {character} '"' | {character except whitespace and ")"}
+ * pattern = "/" {character} "/"
+ * comparison = field operator value | field ("~" | "!~") pattern | "(" expression ")"
+ * conjunction = comparison {"and" comparison}
+ * expression = conjunction {"or" conjunction}
+ */
+fn skip_whitespace(it: &mut std::str::Chars) {
+    *it = it.as_str().trim_start().chars();
+}
+
+fn parse_keyword(it: &mut std::str::Chars, keyword: &str) -> bool {
+    skip_whitespace(it);
+    match it.as_str().strip_prefix(keyword) {
+        Some(rest) => {
+            *it = rest.chars();
+            true
+        }
+        None => false,
+    }
+}
+
+fn parse_field_ref(it: &mut std::str::Chars) -> Option<FieldRef> {
+    let mut peek = it.clone();
+    if !parse_keyword(&mut peek, "F") {
+        return None;
+    }
+    let field = if parse_keyword(&mut peek, "{") {
+        let rest = peek.as_str();
+        let end = rest.find('}')?;
+        peek = rest[end + 1..].chars();
+        FieldRef::Name(rest[..end].to_string())
+    } else {
+        FieldRef::Index(parse_integer(&mut peek)?)
+    };
+    *it = peek;
+    Some(field)
+}
+
+fn parse_operator(it: &mut std::str::Chars) -> Option<Operator> {
+    let operators = vec![
+        ("==", Operator::Equal),
+        ("!=", Operator::NotEqual),
+        ("<=", Operator::LessOrEqual),
+        (">=", Operator::GreaterOrEqual),
+        ("<", Operator::Less),
+        (">", Operator::Greater),
+    ];
+    operators
+        .into_iter()
+        .find(|(s, _)| parse_keyword(it, s))
+        .map(|(_, op)| op)
+}
+
+fn parse_value(it: &mut std::str::Chars) -> Option<String> {
+    skip_whitespace(it);
+    let rest = it.as_str();
+    let (value, tail) = if let Some(quoted) = rest.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        (&quoted[..end], &quoted[end + 1..])
+    } else {
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == ')')
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        rest.split_at(end)
+    };
+    *it = tail.chars();
+    Some(value.to_string())
+}
+
+// A '/' inside the pattern could be escaped with a backslash.
+fn parse_pattern(it: &mut std::str::Chars) -> Result<Pattern, &'static str> {
+    if !parse_keyword(it, "/") {
+        return Err("Pattern must be enclosed in '/'");
+    }
+    let mut pattern = String::new();
+    loop {
+        match it.next() {
+            Some('/') => break,
+            Some('\\') if it.as_str().starts_with('/') => pattern.push(it.next().unwrap()),
+            Some(c) => pattern.push(c),
+            None => return Err("Unterminated pattern"),
+        }
+    }
+    Pattern::new(&pattern)
+}
+
+fn parse_comparison(it: &mut std::str::Chars) -> Result<Predicate, &'static str> {
+    if parse_keyword(it, "(") {
+        let predicate = parse_expression(it)?;
+        if !parse_keyword(it, ")") {
+            return Err("Missing ')' in the predicate");
+        }
+        return Ok(predicate);
+    }
+    let field = parse_field_ref(it).ok_or("Field expected in the predicate")?;
+    if parse_keyword(it, "!~") {
+        return Ok(Predicate::NotMatch(field, parse_pattern(it)?));
+    }
+    if parse_keyword(it, "~") {
+        return Ok(Predicate::Match(field, parse_pattern(it)?));
+    }
+    let operator = parse_operator(it).ok_or("Operator expected in the predicate")?;
+    let value = parse_value(it).ok_or("Value expected in the predicate")?;
+    Ok(Predicate::Compare(field, operator, value))
+}
+
+fn parse_conjunction(it: &mut std::str::Chars) -> Result<Predicate, &'static str> {
+    let mut predicate = parse_comparison(it)?;
+    while parse_keyword(it, "and") {
+        predicate = Predicate::And(Box::new(predicate), Box::new(parse_comparison(it)?));
+    }
+    Ok(predicate)
+}
+
+fn parse_expression(it: &mut std::str::Chars) -> Result<Predicate, &'static str> {
+    let mut predicate = parse_conjunction(it)?;
+    while parse_keyword(it, "or") {
+        predicate = Predicate::Or(Box::new(predicate), Box::new(parse_conjunction(it)?));
+    }
+    Ok(predicate)
+}
+
+pub fn parse_predicate(input: &str) -> Result<Predicate, &'static str> {
+    let mut it = input.chars();
+    let predicate = parse_expression(&mut it)?;
+    skip_whitespace(&mut it);
+    if it.next().is_some() {
+        return Err("Unable to fully parse the predicate");
+    }
+    Ok(predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare(field: FieldRef, operator: Operator, value: &str) -> Predicate {
+        Predicate::Compare(field, operator, value.to_string())
+    }
+
+    #[test]
+    fn parse_field_ref_test() {
+        let mut it = "F-1>".chars();
+        assert_eq!(parse_field_ref(&mut it), Some(FieldRef::Index(-1)));
+        assert_eq!(it.as_str(), ">");
+        let mut it = "F{USER}==".chars();
+        assert_eq!(
+            parse_field_ref(&mut it),
+            Some(FieldRef::Name("USER".to_string()))
+        );
+        assert_eq!(it.as_str(), "==");
+        assert_eq!(parse_field_ref(&mut "f1".chars()), None);
+        assert_eq!(parse_field_ref(&mut "F{USER".chars()), None);
+    }
+
+    #[test]
+    fn parse_predicate_test() {
+        assert_eq!(
+            parse_predicate("F4>1000"),
+            Ok(compare(FieldRef::Index(4), Operator::Greater, "1000"))
+        );
+        assert_eq!(
+            parse_predicate("F0~/^d/ and F{USER} == \"root user\" or F1<=2"),
+            Ok(Predicate::Or(
+                Box::new(Predicate::And(
+                    Box::new(Predicate::Match(
+                        FieldRef::Index(0),
+                        Pattern::new("^d").unwrap()
+                    )),
+                    Box::new(compare(
+                        FieldRef::Name("USER".to_string()),
+                        Operator::Equal,
+                        "root user"
+                    )),
+                )),
+                Box::new(compare(FieldRef::Index(1), Operator::LessOrEqual, "2")),
+            ))
+        );
+        assert_eq!(
+            parse_predicate("F0!~/a\\/b/ and (F1!=x or F2>=y)"),
+            Ok(Predicate::And(
+                Box::new(Predicate::NotMatch(
+                    FieldRef::Index(0),
+                    Pattern::new("a/b").unwrap()
+                )),
+                Box::new(Predicate::Or(
+                    Box::new(compare(FieldRef::Index(1), Operator::NotEqual, "x")),
+                    Box::new(compare(FieldRef::Index(2), Operator::GreaterOrEqual, "y")),
+                )),
+            ))
+        );
+        assert!(parse_predicate("F1>").is_err());
+        assert!(parse_predicate("F1 1").is_err());
+        assert!(parse_predicate("(F1>1").is_err());
+        assert!(parse_predicate("F1~/abc").is_err());
+        assert!(parse_predicate("F1>1 and").is_err());
+        assert!(parse_predicate("F1>1 F2>2").is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use super::grid_slice_parser::parse_integer;
+
+#[derive(Debug, PartialEq)]
+pub enum TemplatePart {
+    Literal(String),
+    // All the cells selected by the slice joined with a space.
+    Cells,
+    // A field of the original line.
+    Field(i64),
+    LineNumber,
+    FileName,
+}
+
+/*
+ * literal = any character except "{" and "}" | "{{" | "}}"
+ * field = ("f" | "F") integer
+ * placeholder = "{" ["l" | "file" | field] "}"
+ * template = {literal | placeholder}
+ */
+#[derive(Debug, PartialEq)]
+pub struct Template {
+    pub parts: Vec<TemplatePart>,
+}
+
+fn parse_keyword(it: &mut std::str::Chars, keyword: &str) -> bool {
+    match it.as_str().strip_prefix(keyword) {
+        Some(rest) => {
+            *it = rest.chars();
+            true
+        }
+        None => false,
+    }
+}
+
+fn parse_placeholder(it: &mut std::str::Chars) -> Option<TemplatePart> {
+    let mut peek = it.clone();
+    if peek.next()? != '{' {
+        return None;
+    }
+    let part = if parse_keyword(&mut peek, "file") {
+        TemplatePart::FileName
+    } else if parse_keyword(&mut peek, "l") {
+        TemplatePart::LineNumber
+    } else if parse_keyword(&mut peek, "f") || parse_keyword(&mut peek, "F") {
+        TemplatePart::Field(parse_integer(&mut peek)?)
+    } else {
+        TemplatePart::Cells
+    };
+    if peek.next()? != '}' {
+        return None;
+    }
+    *it = peek;
+    Some(part)
+}
+
+pub fn parse_template(input: &str) -> Result<Template, &'static str> {
+    let mut it = input.chars();
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    loop {
+        if parse_keyword(&mut it, "{{") {
+            literal.push('{');
+        } else if parse_keyword(&mut it, "}}") {
+            literal.push('}');
+        } else if let Some(part) = parse_placeholder(&mut it) {
+            if !literal.is_empty() {
+                parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+            }
+            parts.push(part);
+        } else {
+            match it.next() {
+                Some('{') | Some('}') => return Err("Unable to parse the format template"),
+                Some(c) => literal.push(c),
+                None => break,
+            }
+        }
+    }
+    if !literal.is_empty() {
+        parts.push(TemplatePart::Literal(literal));
+    }
+    Ok(Template { parts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(s: &str) -> TemplatePart {
+        TemplatePart::Literal(s.to_string())
+    }
+
+    #[test]
+    fn parse_placeholder_test() {
+        fn parse_placeholder_helper(
+            input: &str,
+            expects: Option<TemplatePart>,
+            next: Option<char>,
+        ) {
+            let mut it = input.chars();
+            assert_eq!(parse_placeholder(&mut it), expects);
+            assert_eq!(it.next(), next);
+        }
+        parse_placeholder_helper("{}a", Some(TemplatePart::Cells), Some('a'));
+        parse_placeholder_helper("{l}", Some(TemplatePart::LineNumber), None);
+        parse_placeholder_helper("{file}", Some(TemplatePart::FileName), None);
+        parse_placeholder_helper("{f3}", Some(TemplatePart::Field(3)), None);
+        parse_placeholder_helper("{F-1}:", Some(TemplatePart::Field(-1)), Some(':'));
+        parse_placeholder_helper("{f}", None, Some('{'));
+        parse_placeholder_helper("{l1}", None, Some('{'));
+        parse_placeholder_helper("{x}", None, Some('{'));
+        parse_placeholder_helper("{f1", None, Some('{'));
+    }
+
+    #[test]
+    fn parse_template_test() {
+        assert_eq!(
+            parse_template("{f0}:{F-1} ({l})"),
+            Ok(Template {
+                parts: vec![
+                    TemplatePart::Field(0),
+                    lit(":"),
+                    TemplatePart::Field(-1),
+                    lit(" ("),
+                    TemplatePart::LineNumber,
+                    lit(")"),
+                ]
+            })
+        );
+        assert_eq!(
+            parse_template("{{{}}}{file}"),
+            Ok(Template {
+                parts: vec![
+                    lit("{"),
+                    TemplatePart::Cells,
+                    lit("}"),
+                    TemplatePart::FileName
+                ]
+            })
+        );
+        assert_eq!(parse_template(""), Ok(Template { parts: vec![] }));
+        assert!(parse_template("{f}").is_err());
+        assert!(parse_template("a}b").is_err());
+    }
+}
@@ -0,0 +1,234 @@
+// A small regular expression engine. It supports literals, `.`, character classes
+// (`[a-z]`, `[^0-9]`, `\d`, `\w`, `\s` and their negated uppercase versions), the `*`,
+// `+` and `?` quantifiers and the `^`/`$` anchors. Groups and alternation are not
+// supported.
+
+#[derive(Clone, Debug, PartialEq)]
+enum Atom {
+    Char(char),
+    Any,
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl Atom {
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Atom::Char(a) => *a == c,
+            Atom::Any => true,
+            Atom::Class { negated, ranges } => {
+                ranges.iter().any(|(from, to)| *from <= c && c <= *to) != *negated
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Repeat {
+    One,
+    ZeroOrOne,
+    ZeroOrMore,
+    OneOrMore,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Node {
+    atom: Atom,
+    repeat: Repeat,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pattern {
+    nodes: Vec<Node>,
+    anchored_start: bool,
+    anchored_end: bool,
+}
+
+fn escaped_class(c: char) -> Option<Atom> {
+    let ranges = match c.to_ascii_lowercase() {
+        'd' => vec![('0', '9')],
+        'w' => vec![('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')],
+        's' => vec![(' ', ' '), ('\t', '\r')],
+        _ => return None,
+    };
+    Some(Atom::Class {
+        negated: c.is_uppercase(),
+        ranges,
+    })
+}
+
+fn parse_class(it: &mut std::str::Chars) -> Result<Atom, &'static str> {
+    let mut negated = false;
+    let mut ranges = Vec::new();
+    let mut peek = it.clone();
+    if peek.next() == Some('^') {
+        negated = true;
+        *it = peek;
+    }
+    // ']' right after '[' or '[^' is treated as a literal.
+    let mut first = true;
+    loop {
+        let c = match it.next() {
+            Some(']') if !first => break,
+            Some('\\') => it.next().ok_or("Unterminated character class")?,
+            Some(c) => c,
+            None => return Err("Unterminated character class"),
+        };
+        first = false;
+        let mut peek = it.clone();
+        match (peek.next(), peek.next()) {
+            (Some('-'), Some(to)) if to != ']' => {
+                ranges.push((c, to));
+                *it = peek;
+            }
+            _ => ranges.push((c, c)),
+        }
+    }
+    Ok(Atom::Class { negated, ranges })
+}
+
+impl Pattern {
+    pub fn new(input: &str) -> Result<Self, &'static str> {
+        let mut pattern = Pattern {
+            nodes: Vec::new(),
+            anchored_start: false,
+            anchored_end: false,
+        };
+        let mut it = input.chars();
+        let mut peek = it.clone();
+        if peek.next() == Some('^') {
+            pattern.anchored_start = true;
+            it = peek;
+        }
+        while let Some(c) = it.next() {
+            let repeat = match c {
+                '*' => Some(Repeat::ZeroOrMore),
+                '+' => Some(Repeat::OneOrMore),
+                '?' => Some(Repeat::ZeroOrOne),
+                _ => None,
+            };
+            if let Some(repeat) = repeat {
+                match pattern.nodes.last_mut() {
+                    Some(node) if node.repeat == Repeat::One => node.repeat = repeat,
+                    _ => return Err("Nothing to repeat in the pattern"),
+                }
+                continue;
+            }
+            let atom = match c {
+                '$' if it.as_str().is_empty() => {
+                    pattern.anchored_end = true;
+                    break;
+                }
+                '.' => Atom::Any,
+                '[' => parse_class(&mut it)?,
+                '\\' => {
+                    let c = it.next().ok_or("Trailing backslash in the pattern")?;
+                    escaped_class(c).unwrap_or(match c {
+                        't' => Atom::Char('\t'),
+                        'n' => Atom::Char('\n'),
+                        _ => Atom::Char(c),
+                    })
+                }
+                c => Atom::Char(c),
+            };
+            pattern.nodes.push(Node {
+                atom,
+                repeat: Repeat::One,
+            });
+        }
+        Ok(pattern)
+    }
+
+    // Steps through the text once keeping the set of nodes that could match the next
+    // character, so the time doesn't depend on how the quantifiers are nested. State
+    // `nodes.len()` means that the whole pattern has matched.
+    pub fn is_match(&self, text: &str) -> bool {
+        let accept = self.nodes.len();
+        let mut current = vec![false; accept + 1];
+        let mut next = vec![false; accept + 1];
+        self.add_state(&mut current, 0);
+        for c in text.chars() {
+            if current[accept] && !self.anchored_end {
+                return true;
+            }
+            next.iter_mut().for_each(|s| *s = false);
+            for (n, node) in self.nodes.iter().enumerate() {
+                if !current[n] || !node.atom.matches(c) {
+                    continue;
+                }
+                if let Repeat::ZeroOrMore | Repeat::OneOrMore = node.repeat {
+                    self.add_state(&mut next, n);
+                }
+                self.add_state(&mut next, n + 1);
+            }
+            if !self.anchored_start {
+                self.add_state(&mut next, 0);
+            }
+            std::mem::swap(&mut current, &mut next);
+        }
+        current[accept]
+    }
+
+    // Adds `state` and the states after it that could be reached by skipping optional
+    // nodes.
+    fn add_state(&self, states: &mut [bool], mut state: usize) {
+        while !states[state] {
+            states[state] = true;
+            match self.nodes.get(state) {
+                Some(node)
+                    if node.repeat == Repeat::ZeroOrOne || node.repeat == Repeat::ZeroOrMore =>
+                {
+                    state += 1
+                }
+                _ => return,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_match(pattern: &str, text: &str) -> bool {
+        Pattern::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn pattern_test() {
+        assert!(is_match("^d", "dr-xr-xr-x"));
+        assert!(!is_match("^d", "-r--r--r--"));
+        assert!(is_match("info$", "slabinfo"));
+        assert!(!is_match("info$", "infos"));
+        assert!(is_match("^$", ""));
+        assert!(is_match("s.s", "sysrq"));
+        assert!(is_match("^\\d+:\\d\\d$", "12:54"));
+        assert!(!is_match("^\\d+:\\d\\d$", "12:5"));
+        assert!(is_match("^[a-c]*x?[^0-9]$", "abcaz"));
+        assert!(!is_match("^[a-c]*x?[^0-9]$", "abca9"));
+        assert!(is_match("[]]", "a]"));
+        assert!(is_match("a\\.b", "a.b"));
+        assert!(!is_match("a\\.b", "acb"));
+        assert!(is_match("^\\S+\\s\\w", "ab c"));
+        assert!(is_match("a+b", "xaab"));
+        assert!(!is_match("^a+b", "b"));
+        assert!(is_match("^a?a?aa$", "aa"));
+        assert!(Pattern::new("*a").is_err());
+        assert!(Pattern::new("a**").is_err());
+        assert!(Pattern::new("[a-").is_err());
+        assert!(Pattern::new("a\\").is_err());
+    }
+
+    #[test]
+    fn nested_quantifiers_test() {
+        // Took exponential time with backtracking.
+        let text = "a".repeat(1000);
+        assert!(!is_match("a*a*a*a*a*a*a*b", &text));
+        let pattern = format!("^{}{}$", "a?".repeat(30), "a".repeat(30));
+        assert!(!is_match(&pattern, &"a".repeat(29)));
+        assert!(is_match(&pattern, &"a".repeat(45)));
+        assert!(is_match("a*a*a*a*a*a*a*$", &text));
+    }
+}
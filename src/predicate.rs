@@ -0,0 +1,114 @@
+use std::cmp::Ordering;
+
+use crate::grid_slice::normalize_index;
+use crate::parser::predicate_parser::{FieldRef, Operator, Predicate};
+
+#[derive(Clone)]
+pub struct LineFilter {
+    predicate: Predicate,
+    // Fields of the first line that are used to resolve field names.
+    header: Vec<String>,
+}
+
+// Values are compared as numbers if both of them are finite numbers and as text
+// otherwise, so `nan` and `inf` are not equal to every number.
+fn compare_values(a: &str, b: &str) -> Ordering {
+    let number = |v: &str| v.parse::<f64>().ok().filter(|v| v.is_finite());
+    match (number(a), number(b)) {
+        (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or_else(|| a.cmp(b)),
+        _ => a.cmp(b),
+    }
+}
+
+impl LineFilter {
+    pub fn new(predicate: Predicate) -> Self {
+        LineFilter {
+            predicate,
+            header: Vec::new(),
+        }
+    }
+
+    pub fn set_header<S: AsRef<str>>(&mut self, fields: &[S]) {
+        self.header = fields.iter().map(|f| f.as_ref().to_string()).collect();
+    }
+
+    // Returns None if the field doesn't exist in the line.
+    fn get_field<'a, S: AsRef<str>>(&self, field: &FieldRef, fields: &'a [S]) -> Option<&'a str> {
+        let index = match field {
+            FieldRef::Index(i) => normalize_index(*i, fields.len())?,
+            FieldRef::Name(name) => self.header.iter().position(|h| h == name)?,
+        };
+        fields.get(index).map(|f| f.as_ref())
+    }
+
+    fn evaluate<S: AsRef<str>>(&self, predicate: &Predicate, fields: &[S]) -> bool {
+        match predicate {
+            Predicate::Compare(field, operator, value) => {
+                let field = match self.get_field(field, fields) {
+                    Some(f) => f,
+                    None => return false,
+                };
+                let ordering = compare_values(field, value);
+                match operator {
+                    Operator::Equal => ordering == Ordering::Equal,
+                    Operator::NotEqual => ordering != Ordering::Equal,
+                    Operator::Less => ordering == Ordering::Less,
+                    Operator::LessOrEqual => ordering != Ordering::Greater,
+                    Operator::Greater => ordering == Ordering::Greater,
+                    Operator::GreaterOrEqual => ordering != Ordering::Less,
+                }
+            }
+            Predicate::Match(field, pattern) => self
+                .get_field(field, fields)
+                .is_some_and(|f| pattern.is_match(f)),
+            Predicate::NotMatch(field, pattern) => self
+                .get_field(field, fields)
+                .is_some_and(|f| !pattern.is_match(f)),
+            Predicate::And(a, b) => self.evaluate(a, fields) && self.evaluate(b, fields),
+            Predicate::Or(a, b) => self.evaluate(a, fields) || self.evaluate(b, fields),
+        }
+    }
+
+    // Comparisons of fields that don't exist in the line are always false.
+    pub fn matches<S: AsRef<str>>(&self, fields: &[S]) -> bool {
+        self.evaluate(&self.predicate, fields)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::predicate_parser::parse_predicate;
+
+    fn fields(fields: &str) -> Vec<String> {
+        fields.split_whitespace().map(|f| f.to_string()).collect()
+    }
+
+    fn matches(predicate: &str, line: &str) -> bool {
+        let mut filter = LineFilter::new(parse_predicate(predicate).unwrap());
+        filter.set_header(&fields("USER PID COMMAND"));
+        filter.matches(&fields(line))
+    }
+
+    #[test]
+    fn line_filter_test() {
+        assert!(matches("F1>1000", "root 4096 init"));
+        assert!(!matches("F1>1000", "root 999 init"));
+        assert!(matches("F1>=999 and F1<=999", "root 999 init"));
+        assert!(matches("F-1<=ab", "root 1 aa"));
+        assert!(matches("F0~/^r/", "root 1 init"));
+        assert!(!matches("F0!~/^r/", "root 1 init"));
+        assert!(matches("F{USER}==root", "root 1 init"));
+        assert!(!matches("F{USER}!=root", "root 1 init"));
+        assert!(!matches("F{TTY}!=root", "root 1 init"));
+        assert!(!matches("F5!=root", "root 1 init"));
+        assert!(matches("F5==x or F2==init", "root 1 init"));
+        assert!(!matches("(F5==x or F2==init) and F0==user", "root 1 init"));
+        // NaN and infinities are compared as text.
+        assert!(!matches("F1==3", "a nan"));
+        assert!(!matches("F1<=3 and F1>=3", "a NaN"));
+        assert!(matches("F1==nan", "a nan"));
+        assert!(!matches("F1<3", "a inf"));
+        assert!(matches("F1>3", "a 3.5"));
+    }
+}
@@ -0,0 +1,150 @@
+use std::io::{self, BufRead, Read};
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+use crate::grid_slice::Cell;
+
+// Reads the next record into `record` without the separator. Multi-byte separators are
+// found by reading up to their last byte until the record ends with the whole
+// separator. CRLF line endings are stripped the same way as the separator itself.
+// Returns false at the end of the input.
+pub fn read_record<R: BufRead>(
+    reader: &mut R,
+    separator: &[u8],
+    record: &mut Vec<u8>,
+) -> io::Result<bool> {
+    let last = match separator.last() {
+        Some(b) => *b,
+        None => return Ok(false),
+    };
+    record.clear();
+    let mut read = false;
+    while reader.read_until(last, record)? > 0 {
+        read = true;
+        if record.ends_with(separator) {
+            record.truncate(record.len() - separator.len());
+            break;
+        }
+    }
+    if separator == b"\n" && record.ends_with(b"\r") {
+        record.pop();
+    }
+    Ok(read)
+}
+
+// Fields are always owned, so they could be used as cells of any lifetime.
+pub struct SplitLines<'a, I: BufRead> {
+    source: I,
+    separator: Vec<u8>,
+    cells: PhantomData<Cell<'a>>,
+}
+
+impl<I: BufRead> SplitLines<'_, I> {
+    pub fn new(source: I, separator: &[u8]) -> Self {
+        SplitLines {
+            source,
+            separator: separator.to_vec(),
+            cells: PhantomData,
+        }
+    }
+}
+
+impl<'a, I: BufRead> Iterator for SplitLines<'a, I> {
+    type Item = Vec<Cell<'a>>;
+
+    // Invalid UTF-8 is replaced.
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = Vec::new();
+        if !read_record(&mut self.source, &self.separator, &mut record).ok()? {
+            return None;
+        }
+        Some(
+            String::from_utf8_lossy(&record)
+                .split_whitespace()
+                .map(|f| Cell::Owned(f.to_string()))
+                .collect(),
+        )
+    }
+}
+
+// Records are read by iterators that stop at the first error, so the reader remembers
+// whether it has failed and the error could be reported once the input is processed.
+pub struct CheckedReader<R> {
+    inner: R,
+    failed: Rc<std::cell::Cell<bool>>,
+}
+
+impl<R> CheckedReader<R> {
+    pub fn new(inner: R, failed: Rc<std::cell::Cell<bool>>) -> Self {
+        CheckedReader { inner, failed }
+    }
+}
+
+fn check<T>(result: io::Result<T>, failed: &std::cell::Cell<bool>) -> io::Result<T> {
+    if let Err(ref e) = result {
+        // Interrupted reads are retried.
+        if e.kind() != io::ErrorKind::Interrupted {
+            failed.set(true);
+        }
+    }
+    result
+}
+
+impl<R: Read> Read for CheckedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        check(self.inner.read(buf), &self.failed)
+    }
+}
+
+impl<R: BufRead> BufRead for CheckedReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        check(self.inner.fill_buf(), &self.failed)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(input: &str, separator: &str) -> Vec<Vec<String>> {
+        SplitLines::new(input.as_bytes(), separator.as_bytes())
+            .map(|l| l.into_iter().map(|c| c.into_owned()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn split_lines_test() {
+        assert_eq!(split("a b\nc\n", "\n"), vec![vec!["a", "b"], vec!["c"]]);
+        assert_eq!(
+            split("a b\0c d\ne", "\0"),
+            vec![vec!["a", "b"], vec!["c", "d", "e"]]
+        );
+        assert_eq!(split("a;b;;c;d;;", ";;"), vec![vec!["a;b"], vec!["c;d"]]);
+        assert!(split("", "\n").is_empty());
+        assert_eq!(split("a \r\nb\r\n", "\n"), vec![vec!["a"], vec!["b"]]);
+    }
+
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            Err(io::Error::other("failed"))
+        }
+    }
+
+    #[test]
+    fn checked_reader_test() {
+        let failed = Rc::new(std::cell::Cell::new(false));
+        let reader = CheckedReader::new(io::BufReader::new(FailingReader), failed.clone());
+        assert_eq!(SplitLines::new(reader, b"\n").count(), 0);
+        assert!(failed.get());
+        let reader = CheckedReader::new(&b"a\n"[..], failed.clone());
+        failed.set(false);
+        assert_eq!(SplitLines::new(reader, b"\n").count(), 1);
+        assert!(!failed.get());
+    }
+}
@@ -0,0 +1,228 @@
+use std::cmp::Ordering;
+
+use crate::grid_slice::{self, Cell, Line};
+use crate::parser::grid_slice_parser::GridSliceFilter;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SortMode {
+    #[default]
+    Text,
+    Numeric,
+    // Numbers with an optional K/M/G/T/P/E suffix (e.g. `4.0K`).
+    HumanSize,
+    // Digit sequences are compared as numbers (e.g. `1.2` < `1.10`).
+    Version,
+    // The same as Version but month names are treated as numbers and punctuation is
+    // ignored.
+    Date,
+}
+
+pub fn parse_sort_mode(input: &str) -> Result<SortMode, &'static str> {
+    match input {
+        "text" => Ok(SortMode::Text),
+        "numeric" => Ok(SortMode::Numeric),
+        "human" => Ok(SortMode::HumanSize),
+        "version" => Ok(SortMode::Version),
+        "date" => Ok(SortMode::Date),
+        _ => Err("Unknown sort mode"),
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SortOptions {
+    pub mode: SortMode,
+    pub reverse: bool,
+    // Don't compare whole lines when keys are equal.
+    pub stable: bool,
+    // Keep only the first line out of lines with equal keys.
+    pub unique: bool,
+}
+
+// NaN and infinities are not numbers, otherwise the values wouldn't be totally ordered.
+fn parse_number(cell: &str) -> Option<f64> {
+    cell.parse::<f64>().ok().filter(|v| v.is_finite())
+}
+
+fn parse_human_size(cell: &str) -> Option<f64> {
+    let suffixes = ['K', 'M', 'G', 'T', 'P', 'E'];
+    let (number, multiplier) = match cell.chars().last() {
+        Some(c) => match suffixes.iter().position(|s| *s == c.to_ascii_uppercase()) {
+            Some(p) => (&cell[..cell.len() - 1], 1024f64.powi(p as i32 + 1)),
+            None => (cell, 1.0),
+        },
+        None => return None,
+    };
+    parse_number(number)
+        .map(|n| n * multiplier)
+        .filter(|v| v.is_finite())
+}
+
+// Numbers are ordered after everything that is not a number.
+fn compare_numbers(a: Option<f64>, b: Option<f64>) -> Option<Ordering> {
+    match (a, b) {
+        (Some(a), Some(b)) => a.partial_cmp(&b),
+        (Some(_), None) => Some(Ordering::Greater),
+        (None, Some(_)) => Some(Ordering::Less),
+        (None, None) => None,
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+const MONTH_NUMBERS: [&str; 12] = [
+    "1", "2", "3", "4", "5", "6", "7", "8", "9", "10", "11", "12",
+];
+
+#[derive(Debug, PartialEq)]
+enum Token<'a> {
+    // Digits without leading zeros.
+    Number(&'a str),
+    Text(&'a str),
+}
+
+fn tokenize(cell: &str, date: bool) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = cell;
+    while let Some(c) = rest.chars().next() {
+        let len = rest
+            .find(|n: char| {
+                if c.is_ascii_digit() {
+                    !n.is_ascii_digit()
+                } else if c.is_alphabetic() && date {
+                    !n.is_alphabetic()
+                } else {
+                    n.is_ascii_digit() || (date && n.is_alphabetic())
+                }
+            })
+            .unwrap_or(rest.len());
+        let (token, tail) = rest.split_at(len);
+        rest = tail;
+        if c.is_ascii_digit() {
+            let digits = token.trim_start_matches('0');
+            tokens.push(Token::Number(if digits.is_empty() { "0" } else { digits }));
+        } else if !date {
+            tokens.push(Token::Text(token));
+        } else if c.is_alphabetic() {
+            let name = token.to_ascii_lowercase();
+            // Both full and abbreviated month names are recognized.
+            match MONTHS
+                .iter()
+                .position(|m| name.len() >= 3 && m.starts_with(&name))
+            {
+                Some(p) => tokens.push(Token::Number(MONTH_NUMBERS[p])),
+                None => tokens.push(Token::Text(token)),
+            }
+        }
+    }
+    tokens
+}
+
+fn compare_tokens(a: &Token, b: &Token) -> Ordering {
+    match (a, b) {
+        (Token::Number(a), Token::Number(b)) => a.len().cmp(&b.len()).then(a.cmp(b)),
+        (Token::Number(_), Token::Text(_)) => Ordering::Less,
+        (Token::Text(_), Token::Number(_)) => Ordering::Greater,
+        (Token::Text(a), Token::Text(b)) => a.cmp(b),
+    }
+}
+
+fn compare_natural(a: &str, b: &str, date: bool) -> Ordering {
+    let a = tokenize(a, date);
+    let b = tokenize(b, date);
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| compare_tokens(a, b))
+        .find(|o| *o != Ordering::Equal)
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+fn compare_cells(a: &str, b: &str, mode: SortMode) -> Ordering {
+    let ordering = match mode {
+        SortMode::Text => None,
+        SortMode::Numeric => compare_numbers(parse_number(a), parse_number(b)),
+        SortMode::HumanSize => compare_numbers(parse_human_size(a), parse_human_size(b)),
+        SortMode::Version => Some(compare_natural(a, b, false)),
+        SortMode::Date => Some(compare_natural(a, b, true)),
+    };
+    ordering.unwrap_or_else(|| a.cmp(b))
+}
+
+pub fn compare_keys<S: AsRef<str>>(a: &[S], b: &[S], mode: SortMode) -> Ordering {
+    a.iter()
+        .zip(b.iter())
+        .map(|(a, b)| compare_cells(a.as_ref(), b.as_ref(), mode))
+        .find(|o| *o != Ordering::Equal)
+        .unwrap_or_else(|| a.len().cmp(&b.len()))
+}
+
+// Sorts lines by the cells that `key` selects from their fields.
+pub fn sort<'a, I: Iterator<Item = Line<'a>>>(
+    iter: I,
+    key: &GridSliceFilter,
+    options: &SortOptions,
+) -> Vec<Line<'a>> {
+    let mut cache = grid_slice::SliceCache::default();
+    let mut lines: Vec<(Vec<Cell<'a>>, Line<'a>)> = iter
+        .map(|l| (grid_slice::slice_fields(key, &l.fields, &mut cache), l))
+        .collect();
+    lines.sort_by(|(a_key, a), (b_key, b)| {
+        let mut ordering = compare_keys(a_key, b_key, options.mode);
+        if !options.stable {
+            ordering = ordering.then_with(|| a.fields.cmp(&b.fields));
+        }
+        if options.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    });
+    if options.unique {
+        lines.dedup_by(|(a, _), (b, _)| compare_keys(a, b, options.mode) == Ordering::Equal);
+    }
+    lines.into_iter().map(|(_, l)| l).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_cells_test() {
+        use Ordering::*;
+        assert_eq!(compare_cells("10", "9", SortMode::Text), Less);
+        assert_eq!(compare_cells("10", "9", SortMode::Numeric), Greater);
+        assert_eq!(compare_cells("abc", "-1", SortMode::Numeric), Less);
+        assert_eq!(compare_cells("4.0K", "900", SortMode::HumanSize), Greater);
+        assert_eq!(compare_cells("1.1M", "4.0K", SortMode::HumanSize), Greater);
+        assert_eq!(compare_cells("1.1M", "1.1G", SortMode::HumanSize), Less);
+        // NaN and infinities are compared as text before all the numbers.
+        assert_eq!(compare_cells("nan", "3", SortMode::Numeric), Less);
+        assert_eq!(compare_cells("-nan", "+nan", SortMode::Numeric), Greater);
+        assert_eq!(compare_cells("inf", "-5", SortMode::Numeric), Less);
+        assert_eq!(compare_cells("+2", "-nan", SortMode::Numeric), Greater);
+        assert_eq!(compare_cells("NaNK", "1K", SortMode::HumanSize), Less);
+        assert_eq!(compare_cells("1e308K", "1K", SortMode::HumanSize), Less);
+        assert_eq!(compare_cells("1.10", "1.2", SortMode::Version), Greater);
+        assert_eq!(compare_cells("1.2rc1", "1.2", SortMode::Version), Greater);
+        assert_eq!(compare_cells("v01.2", "v1.2", SortMode::Version), Equal);
+        assert_eq!(compare_cells("Mar", "May", SortMode::Date), Less);
+        assert_eq!(compare_cells("Dec", "Feb", SortMode::Date), Greater);
+        assert_eq!(
+            compare_cells("2024-1-6", "2024-01-05T10:00", SortMode::Date),
+            Greater
+        );
+        assert_eq!(compare_cells("9:05", "12:54", SortMode::Date), Less);
+    }
+}
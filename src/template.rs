@@ -0,0 +1,20 @@
+use crate::grid_slice::{normalize_index, Cell, Line};
+use crate::parser::template_parser::{Template, TemplatePart};
+
+fn get_field<'a>(fields: &'a [Cell], index: i64) -> &'a str {
+    normalize_index(index, fields.len()).map_or("", |i| fields[i].as_ref())
+}
+
+pub fn render(template: &Template, line: &Line, file_name: &str) -> String {
+    let mut output = String::new();
+    for part in &template.parts {
+        match part {
+            TemplatePart::Literal(s) => output.push_str(s),
+            TemplatePart::Cells => output.push_str(&line.cells.join(" ")),
+            TemplatePart::Field(i) => output.push_str(get_field(&line.fields, *i)),
+            TemplatePart::LineNumber => output.push_str(&line.number.to_string()),
+            TemplatePart::FileName => output.push_str(file_name),
+        }
+    }
+    output
+}
@@ -0,0 +1,67 @@
+use crate::grid_slice::{Cell, Line};
+
+// Swaps lines and fields of the selected cells. Lines that are shorter than the
+// longest one are padded with `filler`. Each returned line is numbered after the field
+// index it was built from.
+pub fn transpose<'a, I: Iterator<Item = Line<'a>>>(iter: I, filler: &str) -> Vec<Line<'a>> {
+    let rows: Vec<Vec<Cell<'a>>> = iter.map(|l| l.cells).collect();
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut columns: Vec<Vec<Cell<'a>>> = vec![Vec::with_capacity(rows.len()); width];
+    for row in rows {
+        let len = row.len();
+        for (column, cell) in columns.iter_mut().zip(row) {
+            column.push(cell);
+        }
+        for column in columns.iter_mut().skip(len) {
+            column.push(Cell::from(filler.to_string()));
+        }
+    }
+    columns
+        .into_iter()
+        .enumerate()
+        .map(|(number, cells)| Line {
+            number,
+            fields: cells.clone(),
+            cells,
+            selected: true,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(cells: &[&'static str]) -> Line<'static> {
+        let cells: Vec<Cell> = cells.iter().map(|c| Cell::from(*c)).collect();
+        Line {
+            number: 0,
+            fields: cells.clone(),
+            cells,
+            selected: true,
+        }
+    }
+
+    #[test]
+    fn transpose_test() {
+        let lines = vec![line(&["a", "1"]), line(&["b"]), line(&["c", "3", "x"])];
+        let transposed: Vec<(usize, Vec<String>)> = transpose(lines.into_iter(), "-")
+            .into_iter()
+            .map(|l| {
+                (
+                    l.number,
+                    l.cells.into_iter().map(Cell::into_owned).collect(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            transposed,
+            vec![
+                (0, vec!["a".to_string(), "b".to_string(), "c".to_string()]),
+                (1, vec!["1".to_string(), "-".to_string(), "3".to_string()]),
+                (2, vec!["-".to_string(), "-".to_string(), "x".to_string()]),
+            ]
+        );
+        assert!(transpose(Vec::new().into_iter(), "-").is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+use crate::grid_slice::{self, Cell, Line, SliceCache};
+use crate::parser::grid_slice_parser::GridSliceFilter;
+
+#[derive(Debug, Default)]
+pub struct UniqOptions {
+    // Compare each line only with the previous one instead of all the lines seen so far.
+    pub adjacent: bool,
+    // Prefix each line with the number of lines that had the same key.
+    pub count: bool,
+}
+
+// Only hashes of the keys are kept in memory, so lines with different keys could be
+// treated as duplicates in the unlikely case of a hash collision.
+fn hash_key(key: &GridSliceFilter, line: &Line, cache: &mut SliceCache) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    grid_slice::slice_fields(key, &line.fields, cache).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn with_count(mut line: Line, count: usize) -> Line {
+    line.cells.insert(0, Cell::from(count.to_string()));
+    line
+}
+
+struct AdjacentCount<'a, 'k, I> {
+    source: I,
+    key: &'k GridSliceFilter,
+    cache: SliceCache,
+    // The first line of the current group of adjacent lines with the same key.
+    current: Option<(u64, Line<'a>, usize)>,
+}
+
+impl<'a, I: Iterator<Item = Line<'a>>> Iterator for AdjacentCount<'a, '_, I> {
+    type Item = Line<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for line in self.source.by_ref() {
+            let hash = hash_key(self.key, &line, &mut self.cache);
+            match self.current {
+                Some((h, _, ref mut count)) if h == hash => *count += 1,
+                _ => {
+                    if let Some((_, l, count)) = self.current.replace((hash, line, 1)) {
+                        return Some(with_count(l, count));
+                    }
+                }
+            }
+        }
+        self.current
+            .take()
+            .map(|(_, l, count)| with_count(l, count))
+    }
+}
+
+// Drops lines whose cells selected by `key` have been seen before.
+pub fn uniq<'a, I: Iterator<Item = Line<'a>> + 'a>(
+    iter: I,
+    key: &'a GridSliceFilter,
+    options: &UniqOptions,
+) -> Box<dyn Iterator<Item = Line<'a>> + 'a> {
+    let mut cache = SliceCache::default();
+    match (options.adjacent, options.count) {
+        (false, false) => {
+            let mut seen = HashSet::new();
+            Box::new(iter.filter(move |l| seen.insert(hash_key(key, l, &mut cache))))
+        }
+        (false, true) => {
+            // Counts are known only at the end of the input.
+            let mut seen: HashMap<u64, usize> = HashMap::new();
+            let mut lines: Vec<(Line<'a>, usize)> = Vec::new();
+            for line in iter {
+                let n = *seen
+                    .entry(hash_key(key, &line, &mut cache))
+                    .or_insert(lines.len());
+                if n == lines.len() {
+                    lines.push((line, 1));
+                } else {
+                    lines[n].1 += 1;
+                }
+            }
+            Box::new(lines.into_iter().map(|(l, count)| with_count(l, count)))
+        }
+        (true, false) => {
+            let mut last = None;
+            Box::new(iter.filter(move |l| {
+                let hash = Some(hash_key(key, l, &mut cache));
+                let new = hash != last;
+                last = hash;
+                new
+            }))
+        }
+        (true, true) => Box::new(AdjacentCount {
+            source: iter,
+            key,
+            cache,
+            current: None,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::grid_slice_parser::parse_grid_slice;
+
+    fn line(fields: &[&'static str]) -> Line<'static> {
+        let fields: Vec<Cell> = fields.iter().map(|f| Cell::from(*f)).collect();
+        Line {
+            number: 0,
+            cells: fields.clone(),
+            fields,
+            selected: true,
+        }
+    }
+
+    fn run(adjacent: bool, count: bool) -> Vec<String> {
+        let lines = vec![
+            line(&["a", "1"]),
+            line(&["a", "2"]),
+            line(&["b", "3"]),
+            line(&["a", "4"]),
+        ];
+        let key = parse_grid_slice("F0").unwrap();
+        let options = UniqOptions { adjacent, count };
+        uniq(lines.into_iter(), &key, &options)
+            .map(|l| l.cells.join(" "))
+            .collect()
+    }
+
+    #[test]
+    fn uniq_test() {
+        assert_eq!(run(false, false), vec!["a 1", "b 3"]);
+        assert_eq!(run(false, true), vec!["3 a 1", "1 b 3"]);
+        assert_eq!(run(true, false), vec!["a 1", "b 3", "a 4"]);
+        assert_eq!(run(true, true), vec!["2 a 1", "1 b 3", "1 a 4"]);
+    }
+}
@@ -0,0 +1,41 @@
+use std::io::{Read, Write};
+use std::process::{Command, Stdio};
+use std::thread;
+
+// Reads only the beginning of the output and closes the pipe while rtr is still
+// writing to it, the same way `rtr ... | head -1` does.
+fn run_with_early_close(args: &[&str]) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_rtr"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = thread::spawn(move || {
+        let line = b"-r--r--r--  1 root root 0 May  6 12:54 version\n";
+        for _ in 0..200_000 {
+            // rtr might exit before reading the whole input.
+            if stdin.write_all(line).is_err() {
+                break;
+            }
+        }
+    });
+    let mut stdout = child.stdout.take().unwrap();
+    let mut buf = [0; 16];
+    stdout.read_exact(&mut buf).unwrap();
+    drop(stdout);
+
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    assert!(output.stderr.is_empty(), "{:?}", output);
+}
+
+#[test]
+fn broken_pipe_test() {
+    run_with_early_close(&["F-1"]);
+    run_with_early_close(&["--format", "{l}: {f-1}", "l0"]);
+    run_with_early_close(&["-C1", "--where", "F1 == 1", "F-1"]);
+}
@@ -0,0 +1,151 @@
+mod common;
+
+use common::{fixture, rtr};
+
+fn stdout(args: &[&str], input: &str) -> String {
+    let output = rtr(args, input.as_bytes());
+    assert!(output.status.success(), "{:?}: {:?}", args, output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn stderr(args: &[&str], input: &str) -> String {
+    let output = rtr(args, input.as_bytes());
+    assert!(!output.status.success(), "{:?}: {:?}", args, output);
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn file_test() {
+    let proc = std::fs::read_to_string(fixture("proc.txt")).unwrap();
+    let path = fixture("proc.txt");
+    // Mapped files go through a different path than stdin.
+    for slice in &["F-1", "l-3", "l10:l15:l-2", "L0::f-1c-1", "f[-1,2,0]:l1"] {
+        assert_eq!(
+            stdout(&[slice, &path], ""),
+            stdout(&[slice], &proc),
+            "{}",
+            slice
+        );
+    }
+    // Streamed files go through the same path as stdin.
+    assert_eq!(
+        stdout(&["--no-mmap", "--threads", "3", "l1:l3F-1", &path], ""),
+        "sched_debug\nschedstat\nscsi\n"
+    );
+    for threads in &["1", "3"] {
+        assert_eq!(
+            stdout(&["--threads", threads, "l1:l3F-1", &path], ""),
+            "sched_debug\nschedstat\nscsi\n"
+        );
+    }
+}
+
+#[test]
+fn line_numbers_test() {
+    assert_eq!(stdout(&["-n", "l-2F0"], "a\nb\nc\n"), "1:b\n2:c\n");
+    assert_eq!(stdout(&["-n", "F1"], "a b\nc d\n"), "0:b\n1:d\n");
+}
+
+#[test]
+fn record_separator_test() {
+    assert_eq!(stdout(&["-z", "F1"], "a b\0c d\0"), "b\0d\0");
+    assert_eq!(
+        stdout(&["--record-separator", ";", "F0"], "a b;c d"),
+        "a;c;"
+    );
+    // Separators are bytes, so they don't have to be valid UTF-8.
+    let output = rtr(&["--record-separator", "\\xff", "F0"], b"a b\xffc d");
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(output.stdout, b"a\xffc\xff");
+}
+
+#[test]
+fn line_endings_test() {
+    assert_eq!(stdout(&["F1"], "a b\r\nc d\r\n"), "b\nd\n");
+    assert_eq!(
+        stdout(&["--keep-line-endings", "F1"], "a b\r\nc d\r\n"),
+        "b\r\nd\r\n"
+    );
+}
+
+#[test]
+fn where_test() {
+    // The header is compared as text, so it has to be skipped by the slice.
+    let input = "name size\na 10\nb 20\nc 30\n";
+    assert_eq!(
+        stdout(&["--where", "F{size} > 15", "l1F0"], input),
+        "b\nc\n"
+    );
+    // Context lines are printed whole.
+    assert_eq!(
+        stdout(&["-A1", "--where", "F1 == 10", "F0"], input),
+        "a\nb 20\n"
+    );
+}
+
+#[test]
+fn errors_test() {
+    assert!(stderr(&["l1:l"], "").contains("Unable to fully parse the input"));
+    assert!(stderr(&["F1", &fixture("missing.txt")], "").contains("Unable to open a file"));
+    assert!(stderr(&[], "").contains("Wrong number of arguments"));
+    assert!(stderr(&["--follow", "F1"], "").contains("--follow can be used only with a file"));
+}
+
+#[test]
+fn exclusive_test() {
+    let input = "0\n1\n2\n3\n4\n";
+    assert_eq!(stdout(&["--exclusive", "l1:l3"], input), "1\n2\n");
+    assert_eq!(stdout(&["--exclusive", "l-1::l-2"], input), "4\n2\n0\n");
+    assert_eq!(
+        stdout(&["--exclusive", "--normalize", "l0:l-1:l1"], ""),
+        ":l-1\n"
+    );
+}
+
+#[test]
+fn range_set_test() {
+    let input = "abcdefgh ij\nklmnopq rs tu\n";
+    assert_eq!(stdout(&[":c2,c-3"], input), "abcfgh ij\nklmopq rs tu\n");
+    assert_eq!(stdout(&["F0,!C1,f-1"], input), "acdefgh i\nkmnopq t\n");
+    assert_eq!(stdout(&["--normalize", "c0:c2,c-3:c-1"], ""), ":c2,c-3\n");
+    let lines: String = (0..20).map(|n| format!("{}\n", n)).collect();
+    assert_eq!(stdout(&["l1:l2,l10:l11"], &lines), "1\n2\n10\n11\n");
+    assert_eq!(stdout(&[":l1,l-2,!L0"], &lines), "1\n18\n19\n");
+    let path = fixture("proc.txt");
+    assert_eq!(
+        stdout(&["--threads", "3", "L1,L3F-1", &path], ""),
+        "sched_debug\nscsi\n"
+    );
+    assert!(stderr(&["--follow", "l1,l-2", &path], "").contains("could be used with --follow"));
+}
+
+#[test]
+fn transpose_test() {
+    let input = "a 1\nb\nc 3 x\n";
+    let transposed = stdout(&["--transpose", ":"], input);
+    assert_eq!(transposed, "a b c\n1 - 3\n- - x\n");
+    assert_eq!(
+        stdout(&["--transpose", ":"], &transposed),
+        "a 1 -\nb - -\nc 3 x\n"
+    );
+    assert!(stderr(&["--transpose", "--filler", "", ":"], "").contains("--filler cannot be empty"));
+}
+
+#[test]
+fn read_errors_test() {
+    // Directories can be opened but not read.
+    let directory = fixture("");
+    for args in &[
+        vec!["F1"],
+        vec!["--format", "{}", "F1"],
+        vec!["--where", "F0==a", "F1"],
+    ] {
+        let mut args = args.clone();
+        args.push(&directory);
+        assert!(
+            stderr(&args, "").contains("Unable to read the input"),
+            "{:?}",
+            args
+        );
+    }
+}
@@ -0,0 +1,89 @@
+// Runs the same checks as the fuzz target in fuzz/ on random slice-like strings, so
+// the basic properties are checked by `cargo test` as well.
+mod common;
+
+use common::Random;
+use rtr::fast_path::FastPath;
+use rtr::grid_slice::{grid_slice_iter, lines_need_post_process};
+use rtr::parser::grid_slice_parser::{
+    parse_exclusive_grid_slice, parse_grid_slice, GridSliceFilter,
+};
+use rtr::records::SplitLines;
+
+const ITERATIONS: usize = 50_000;
+const INPUT: &str = "a bc def\n\nghij k\nl\n";
+
+// Mostly the characters of the slice syntax with integers that don't fit into i64
+// from time to time.
+fn random_input(random: &mut Random) -> String {
+    let tokens = [
+        "p",
+        "P",
+        "l",
+        "L",
+        "f",
+        "F",
+        "c",
+        "C",
+        "!",
+        ":",
+        "[",
+        "]",
+        ",",
+        "-",
+        "+",
+        "0",
+        "1",
+        "7",
+        "x",
+        " ",
+        "9223372036854775807",
+        "-9223372036854775808",
+        "99999999999999999999",
+    ];
+    (0..random.next(12))
+        .map(|_| tokens[random.next(tokens.len())])
+        .collect()
+}
+
+fn check(input: &str) {
+    check_mode(input, parse_grid_slice);
+    check_mode(input, parse_exclusive_grid_slice);
+}
+
+fn check_mode(input: &str, parse: fn(&str) -> Result<GridSliceFilter, &'static str>) {
+    let grid_slice = match parse(input) {
+        Ok(g) => g,
+        Err(_) => return,
+    };
+    let text = grid_slice.to_string();
+    assert_eq!(parse(&text).as_ref(), Ok(&grid_slice), "{}", input);
+
+    let lines = SplitLines::new(INPUT.as_bytes(), b"\n");
+    if !lines_need_post_process(&grid_slice) {
+        FastPath::new(&grid_slice, b"\n", b"\n", false)
+            .run(INPUT.as_bytes(), 0, &mut Vec::new())
+            .unwrap();
+    }
+    grid_slice_iter(grid_slice, lines).for_each(drop);
+}
+
+#[test]
+fn parser_fuzz_test() {
+    for input in &[
+        "-",
+        "l99999999999999999999",
+        "::l0",
+        "l9223372036854775807",
+        "l-9223372036854775808",
+        "f0:f9223372036854775807:f-9223372036854775808",
+        "!c-9223372036854775808::c9223372036854775807",
+        "f[-9223372036854775808,9223372036854775807]",
+    ] {
+        check(input);
+    }
+    let mut random = Random(1);
+    for _ in 0..ITERATIONS {
+        check(&random_input(&mut random));
+    }
+}
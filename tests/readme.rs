@@ -0,0 +1,113 @@
+// Runs every example from README.md and compares the output with the one shown there,
+// so the documentation can't drift from the behaviour.
+mod common;
+
+use std::fs;
+
+use common::rtr;
+
+const PROC: &str = "ll /proc | tail -20";
+
+struct Example {
+    command: String,
+    output: String,
+}
+
+// Examples are the ```bash blocks that start with a `$ <command>` line followed by the
+// output of the command.
+fn examples(readme: &str) -> Vec<Example> {
+    let mut examples = Vec::new();
+    let mut lines = readme.lines();
+    while let Some(line) = lines.next() {
+        if line != "```bash" {
+            continue;
+        }
+        let block: Vec<&str> = lines.by_ref().take_while(|l| *l != "```").collect();
+        if let Some(command) = block.first().and_then(|l| l.strip_prefix("$ ")) {
+            examples.push(Example {
+                command: command.to_string(),
+                output: block[1..].iter().map(|l| format!("{}\n", l)).collect(),
+            });
+        }
+    }
+    examples
+}
+
+// Splits a command into words the same way a shell does, but only handles quotes.
+fn split_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '\'') | (None, '"') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, ' ') => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    words
+}
+
+// Only the escape sequences used in the examples are supported.
+fn printf(format: &str) -> String {
+    format.replace("\\n", "\n").replace("\\t", "\t")
+}
+
+#[test]
+fn readme_examples_test() {
+    let readme = fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/README.md")).unwrap();
+    let examples = examples(&readme);
+    let proc = &examples
+        .iter()
+        .find(|e| e.command == PROC)
+        .expect("The input of the examples is missing")
+        .output;
+    let mut checked = 0;
+    for example in &examples {
+        let (source, command) = match example.command.rsplit_once(" | rtr ") {
+            Some(c) => c,
+            // Commands without input are checked only if they print something, others
+            // (e.g. `--follow`) wouldn't stop.
+            None => match example.command.strip_prefix("rtr ") {
+                Some(c) if !example.output.is_empty() => ("", c),
+                _ => continue,
+            },
+        };
+        let input = if source.is_empty() {
+            String::new()
+        } else if source == PROC {
+            proc.clone()
+        } else if let Some(format) = source.strip_prefix("printf ") {
+            printf(&split_words(format)[0])
+        } else {
+            panic!("Unknown input of an example: {}", example.command);
+        };
+        let args = split_words(command);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let output = rtr(&args, input.as_bytes());
+        assert!(output.status.success(), "{}: {:?}", example.command, output);
+        assert_eq!(
+            String::from_utf8_lossy(&output.stdout),
+            example.output,
+            "{}",
+            example.command
+        );
+        checked += 1;
+    }
+    assert!(checked >= 20, "Only {} examples were found", checked);
+}
+
+#[test]
+fn split_words_test() {
+    assert_eq!(
+        split_words(r#"--format '{F-1}: {f0}' l-3 "a b"''"#),
+        vec!["--format", "{F-1}: {f0}", "l-3", "a b"]
+    );
+    assert_eq!(split_words("''"), vec![""]);
+}
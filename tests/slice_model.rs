@@ -0,0 +1,443 @@
+// Compares the slicing engine with a reference model of the documented semantics on
+// random grids and slices. The model works like Python's `items[from:to + 1]` with
+// the following intentional differences:
+//
+// * `to` is inclusive and -1 means the last item, so the whole input is `0:-1`.
+// * A negative step reverses the items between `from` and `to` and then takes every
+//   |step|-th of them starting from `to`. `from` still has to come before `to`, so
+//   `f5:f1:f-1` selects nothing while Python's `items[5:1:-1]` doesn't.
+// * "!" selects all the items that the range doesn't, in the order given by the sign
+//   of the step.
+// * Index lists select the listed items in the list order and skip the ones that are
+//   out of bounds instead of failing.
+//
+// With `--exclusive` lowercase ranges are compared with Python's slices directly.
+// Relative `to` endpoints (`f3:f+2`) are resolved against the position of `from`
+// before anything is clamped in both modes. Several ranges of the same dimension
+// (`:c2,c-3`) select the items that any range without "!" selects and all the ranges
+// with "!" leave, in the input order.
+mod common;
+
+use common::Random;
+use rtr::fast_path::FastPath;
+use rtr::grid_slice::{grid_slice_iter, lines_need_post_process};
+use rtr::parser::grid_slice_parser::{parse_exclusive_grid_slice, parse_grid_slice};
+use rtr::records::SplitLines;
+
+const ITERATIONS: usize = 20_000;
+
+fn random_index(random: &mut Random) -> i64 {
+    random.next(15) as i64 - 7
+}
+
+#[derive(Debug)]
+enum Selection {
+    // `l<from>:l<to>:l<step>`, each part is optional.
+    Range {
+        from: Option<i64>,
+        to: Option<i64>,
+        step: Option<i64>,
+    },
+    // `l<from>:l+<offset>:l<step>`.
+    Relative {
+        from: i64,
+        offset: i64,
+        step: Option<i64>,
+    },
+    // `L<index>`, optionally with a step.
+    Single {
+        index: i64,
+        step: Option<i64>,
+    },
+    // `f[<index>,...]`.
+    List(Vec<i64>),
+}
+
+#[derive(Debug)]
+struct Dimension {
+    range: Selection,
+    exclude: bool,
+}
+
+impl Dimension {
+    fn random(random: &mut Random, list: bool) -> Option<Self> {
+        let mut step = || {
+            if random.chance(50) {
+                None
+            } else {
+                Some([-3, -2, -1, 1, 2, 3][random.next(6)])
+            }
+        };
+        let step = step();
+        let range = match random.next(if list { 5 } else { 4 }) {
+            0 => return None,
+            3 => Selection::Relative {
+                from: random_index(random),
+                offset: random.next(5) as i64,
+                step,
+            },
+            1 => Selection::Range {
+                from: Some(random_index(random)).filter(|_| random.chance(70)),
+                to: Some(random_index(random)).filter(|_| random.chance(50)),
+                step,
+            },
+            2 => Selection::Single {
+                index: random_index(random),
+                step,
+            },
+            _ => Selection::List(
+                (0..1 + random.next(4))
+                    .map(|_| random_index(random))
+                    .collect(),
+            ),
+        };
+        // Nothing to put "!" on.
+        if let Selection::Range {
+            from: None,
+            to: None,
+            step: None,
+        } = range
+        {
+            return None;
+        }
+        Some(Dimension {
+            range,
+            exclude: random.chance(20),
+        })
+    }
+
+    // Returns the indexes of the selected items in the order they are printed.
+    fn select(&self, length: usize, exclusive: bool) -> Vec<usize> {
+        let length = length as i64;
+        let normalize = |i: i64| if i < 0 { length + i } else { i };
+        let (from, to, step) = match self.range {
+            Selection::Range { from, to, step } if exclusive => {
+                let step = step.unwrap_or(1);
+                let selected = python_slice(from.map(normalize), to.map(normalize), step, length);
+                return self.exclude_if_needed(selected, step, length);
+            }
+            Selection::Relative { from, offset, step } if exclusive => {
+                let step = step.unwrap_or(1);
+                let (from, to) = (normalize(from), normalize(from) + offset);
+                let selected = python_slice(Some(from), Some(to), step, length);
+                return self.exclude_if_needed(selected, step, length);
+            }
+            Selection::Relative { from, offset, step } => {
+                (normalize(from), normalize(from) + offset, step.unwrap_or(1))
+            }
+            Selection::Range { from, to, step } => (
+                normalize(from.unwrap_or(0)),
+                normalize(to.unwrap_or(-1)),
+                step.unwrap_or(1),
+            ),
+            Selection::Single { index, step } => {
+                (normalize(index), normalize(index), step.unwrap_or(1))
+            }
+            Selection::List(ref list) => {
+                let valid = |i: &i64| (0..length).contains(&normalize(*i));
+                let selected: Vec<usize> = list
+                    .iter()
+                    .filter(|i| valid(i))
+                    .map(|i| normalize(*i) as usize)
+                    .collect();
+                if self.exclude {
+                    return (0..length as usize)
+                        .filter(|i| !selected.contains(i))
+                        .collect();
+                }
+                return selected;
+            }
+        };
+        let mut items: Vec<usize> = (0..length as usize).collect();
+        if step < 0 {
+            items.reverse();
+        }
+        let inside: Vec<usize> = items
+            .into_iter()
+            .filter(|i| from <= *i as i64 && *i as i64 <= to)
+            .collect();
+        let selected: Vec<usize> = inside
+            .into_iter()
+            .step_by(step.unsigned_abs() as usize)
+            .collect();
+        self.exclude_if_needed(selected, step, length)
+    }
+
+    fn step(&self) -> i64 {
+        match self.range {
+            Selection::Range { step, .. }
+            | Selection::Relative { step, .. }
+            | Selection::Single { step, .. } => step.unwrap_or(1),
+            Selection::List(_) => 1,
+        }
+    }
+
+    // Returns the text of `from`, `to` and `step` with "!" before the first endpoint if
+    // the range is excluded.
+    fn endpoints(&self, lower: char, upper: char) -> [Option<String>; 3] {
+        let mut endpoints = match self.range {
+            Selection::Range { from, to, step } => [
+                from.map(|i| format!("{}{}", lower, i)),
+                to.map(|i| format!("{}{}", lower, i)),
+                step.map(|i| format!("{}{}", lower, i)),
+            ],
+            Selection::Relative { from, offset, step } => [
+                Some(format!("{}{}", lower, from)),
+                Some(format!("{}+{}", lower, offset)),
+                step.map(|i| format!("{}{}", lower, i)),
+            ],
+            Selection::Single { index, step } => [
+                Some(format!("{}{}", upper, index)),
+                None,
+                step.map(|i| format!("{}{}", lower, i)),
+            ],
+            Selection::List(ref list) => {
+                let list: Vec<String> = list.iter().map(i64::to_string).collect();
+                [Some(format!("{}[{}]", lower, list.join(","))), None, None]
+            }
+        };
+        if self.exclude {
+            if let Some(e) = endpoints.iter_mut().flatten().next() {
+                e.insert(0, '!');
+            }
+        }
+        endpoints
+    }
+
+    fn exclude_if_needed(&self, selected: Vec<usize>, step: i64, length: i64) -> Vec<usize> {
+        if !self.exclude {
+            return selected;
+        }
+        let mut items: Vec<usize> = (0..length as usize).collect();
+        if step < 0 {
+            items.reverse();
+        }
+        items
+            .into_iter()
+            .filter(|i| !selected.contains(i))
+            .collect()
+    }
+}
+
+// The same as `list(range(length))[from:to:step]` in Python, but the indexes that
+// count from the end have to be converted already.
+fn python_slice(from: Option<i64>, to: Option<i64>, step: i64, length: i64) -> Vec<usize> {
+    let adjust = |i: i64| {
+        if i < 0 {
+            if step < 0 {
+                -1
+            } else {
+                0
+            }
+        } else if i >= length {
+            if step < 0 {
+                length - 1
+            } else {
+                length
+            }
+        } else {
+            i
+        }
+    };
+    let (mut i, end) = if step > 0 {
+        (from.map_or(0, adjust), to.map_or(length, adjust))
+    } else {
+        (from.map_or(length - 1, adjust), to.map_or(-1, adjust))
+    };
+    let mut selected = Vec::new();
+    while (step > 0 && i < end) || (step < 0 && i > end) {
+        selected.push(i as usize);
+        i += step;
+    }
+    selected
+}
+
+struct Slice {
+    // More than one range is a range set.
+    line: Vec<Dimension>,
+    field: Vec<Dimension>,
+    character: Vec<Dimension>,
+}
+
+// Mostly a single range or none, sometimes a range set.
+fn random_ranges(random: &mut Random, list: bool) -> Vec<Dimension> {
+    let count = if random.chance(25) {
+        2 + random.next(2)
+    } else {
+        1
+    };
+    (0..count)
+        .filter_map(|_| Dimension::random(random, list))
+        .collect()
+}
+
+// The items selected by any range without "!" and all the ranges with "!".
+fn select_set(dimensions: &[Dimension], length: usize, exclusive: bool) -> Vec<usize> {
+    match dimensions {
+        [] => (0..length).collect(),
+        [d] => d.select(length, exclusive),
+        _ => {
+            let selections: Vec<(bool, Vec<usize>)> = dimensions
+                .iter()
+                .map(|d| (d.exclude, d.select(length, exclusive)))
+                .collect();
+            let included = selections.iter().filter(|(exclude, _)| !exclude);
+            let all = selections.iter().all(|(exclude, _)| *exclude);
+            (0..length)
+                .filter(|i| all || included.clone().any(|(_, s)| s.contains(i)))
+                .filter(|i| {
+                    selections
+                        .iter()
+                        .filter(|(exclude, _)| *exclude)
+                        .all(|(_, s)| s.contains(i))
+                })
+                .collect()
+        }
+    }
+}
+
+fn join(parts: &[String; 3]) -> String {
+    match parts {
+        [from, to, step] if to.is_empty() && step.is_empty() => from.clone(),
+        [from, to, step] if step.is_empty() => format!("{}:{}", from, to),
+        [from, to, step] => format!("{}:{}:{}", from, to, step),
+    }
+}
+
+impl Slice {
+    fn random(random: &mut Random) -> Self {
+        Slice {
+            line: random_ranges(random, false),
+            field: random_ranges(random, true),
+            character: random_ranges(random, true),
+        }
+    }
+
+    // Sets with negative steps have to be rejected.
+    fn is_valid(&self) -> bool {
+        [&self.line, &self.field, &self.character]
+            .iter()
+            .all(|set| set.len() < 2 || set.iter().all(|d| d.step() > 0))
+    }
+
+    // The first range of each dimension goes into the first slice and the rest of the
+    // ranges are separate slices.
+    fn text(&self) -> String {
+        let mut parts = [String::new(), String::new(), String::new()];
+        let first = [
+            (self.line.first(), 'l', 'L'),
+            (self.field.first(), 'f', 'F'),
+            (self.character.first(), 'c', 'C'),
+        ];
+        for (dimension, lower, upper) in first.iter() {
+            if let Some(d) = dimension {
+                for (part, endpoint) in parts.iter_mut().zip(d.endpoints(*lower, *upper).iter()) {
+                    if let Some(e) = endpoint {
+                        part.push_str(e);
+                    }
+                }
+            }
+        }
+        let mut slices = vec![join(&parts)];
+        let rest = [
+            (&self.line, 'l', 'L'),
+            (&self.field, 'f', 'F'),
+            (&self.character, 'c', 'C'),
+        ];
+        for (set, lower, upper) in rest.iter() {
+            for d in set.iter().skip(1) {
+                let endpoints = d.endpoints(*lower, *upper);
+                slices.push(join(&endpoints.map(Option::unwrap_or_default)));
+            }
+        }
+        slices.join(",")
+    }
+
+    fn apply(&self, grid: &[Vec<String>], exclusive: bool) -> Vec<Vec<String>> {
+        select_set(&self.line, grid.len(), exclusive)
+            .into_iter()
+            .map(|l| {
+                let fields = &grid[l];
+                select_set(&self.field, fields.len(), exclusive)
+                    .into_iter()
+                    .map(|f| {
+                        let chars: Vec<char> = fields[f].chars().collect();
+                        select_set(&self.character, chars.len(), exclusive)
+                            .into_iter()
+                            .map(|c| chars[c])
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+fn random_grid(random: &mut Random) -> Vec<Vec<String>> {
+    let alphabet = ['a', 'b', 'z', 'é', 'д', '€', '日'];
+    (0..random.next(8))
+        .map(|_| {
+            (0..random.next(6))
+                .map(|_| {
+                    (0..1 + random.next(5))
+                        .map(|_| alphabet[random.next(alphabet.len())])
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn slice_model_test() {
+    let mut random = Random(7);
+    let mut checked = 0;
+    for n in 0..ITERATIONS {
+        let exclusive = n % 2 == 1;
+        let slice = Slice::random(&mut random);
+        let text = slice.text();
+        if text.is_empty() {
+            continue;
+        }
+        let grid = random_grid(&mut random);
+        let input: String = grid.iter().map(|l| format!("{}\n", l.join(" "))).collect();
+        let grid_slice = if exclusive {
+            parse_exclusive_grid_slice(&text)
+        } else {
+            parse_grid_slice(&text)
+        };
+        if !slice.is_valid() {
+            assert!(grid_slice.is_err(), "{}", text);
+            continue;
+        }
+        let grid_slice = grid_slice.unwrap_or_else(|e| panic!("{}: {}", text, e));
+        let expected = slice.apply(&grid, exclusive);
+
+        let lines = SplitLines::new(input.as_bytes(), b"\n");
+        let actual: Vec<Vec<String>> = grid_slice_iter(grid_slice.clone(), lines)
+            .map(|l| l.cells.into_iter().map(|c| c.into_owned()).collect())
+            .collect();
+        assert_eq!(actual, expected, "{} ({}) on {:?}", text, exclusive, grid);
+
+        if !lines_need_post_process(&grid_slice) {
+            let mut output = Vec::new();
+            FastPath::new(&grid_slice, b"\n", b"\n", false)
+                .run(input.as_bytes(), 0, &mut output)
+                .unwrap();
+            let expected: String = expected
+                .iter()
+                .map(|l| format!("{}\n", l.join(" ")))
+                .collect();
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                expected,
+                "{} ({}) on {:?}",
+                text,
+                exclusive,
+                grid
+            );
+        }
+        checked += 1;
+    }
+    assert!(checked > ITERATIONS / 2);
+}